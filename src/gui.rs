@@ -0,0 +1,265 @@
+use macroquad::miniquad::window::{clipboard_get, clipboard_set};
+use macroquad::prelude::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use game_of_life::model::formats::{self, Pattern, rle};
+use game_of_life::model::game_of_life::GameOfLife;
+use game_of_life::model::rule::Neighbourhood;
+use game_of_life::model::transform::Transform;
+use game_of_life::model::universe::Engine;
+use game_of_life::view::screen::{Screen, SelectionAction};
+use game_of_life::view::speed::{DEFAULT_SPEED, SPEEDS};
+
+const INITIAL_AREA: (i32, i32, i32, i32) = (-20, -20, 20, 20);
+const RANDOMIZE_AREA: (i32, i32, i32, i32) = (-20, -20, 20, 20);
+
+/// time between checks of the simulation thread while paused
+const PAUSED_SLEEP_TIME: u64 = 10;
+
+const HISTORY_LIMIT: usize = 100;
+
+/// connects the game logic with the view
+/// the pattern, if any, is placed centered on the origin
+/// and simulated with the given engine
+pub async fn run(pattern: Option<Pattern>, engine: Engine) {
+    // use this to avoid quit when the windows is closed
+    prevent_quit();
+
+    let mut screen = Screen::new();
+
+    screen.set_area(
+        INITIAL_AREA.0,
+        INITIAL_AREA.1,
+        INITIAL_AREA.2,
+        INITIAL_AREA.3,
+    );
+
+    let mut initial = GameOfLife::new();
+    // the other engines can't undo
+    match engine {
+        Engine::Sparse => initial.set_history_limit(HISTORY_LIMIT),
+        engine => eprintln!("undo is disabled with the {} engine", engine),
+    }
+    if let Some(pattern) = pattern {
+        if let Some(rule) = &pattern.rule {
+            initial.set_rule(rule.clone());
+        }
+        if let Some(topology) = pattern.topology {
+            initial.set_topology(topology);
+        }
+        let (min_x, min_y, max_x, max_y) = pattern.get_bounding_box().unwrap_or_default();
+        pattern.place(&mut initial, (-(min_x + max_x) / 2, -(min_y + max_y) / 2));
+    }
+    // the main checks the engine supports the pattern before opening the window
+    let initial = engine
+        .build(initial)
+        .expect("engine checked before opening the window");
+    screen.set_boundary(initial.get_topology().get_bounds());
+    screen.set_states(initial.get_rule().get_states());
+    if let Some(table) = initial.get_rule().get_rule_table() {
+        let palette = table
+            .get_colors()
+            .iter()
+            .map(|(&state, &(r, g, b))| (state, Color::from_rgba(r, g, b, 255)));
+        screen.set_palette(palette.collect());
+    }
+    screen.set_hexagonal(initial.get_rule().get_neighbourhood() == Neighbourhood::Hexagonal);
+
+    let gol = Arc::new(RwLock::new(initial));
+    let paused = Arc::new(AtomicBool::new(false));
+    let running = Arc::new(AtomicBool::new(true));
+    let speed = Arc::new(AtomicUsize::new(DEFAULT_SPEED));
+
+    //clone the arcs for the new thread
+    let gol_clone = gol.clone();
+    let paused_clone = paused.clone();
+    let running_clone = running.clone();
+    let speed_clone = speed.clone();
+
+    //Spawn the simulation in a new thread for better performace
+    let join_handle = thread::spawn(move || {
+        //simulation loop
+        loop {
+            if !running_clone.load(Ordering::Relaxed) {
+                break;
+            }
+            if paused_clone.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(PAUSED_SLEEP_TIME));
+                continue;
+            }
+            let speed = SPEEDS[speed_clone.load(Ordering::Relaxed)];
+            {
+                gol_clone.write().unwrap().advance(speed.generations as u64);
+            }
+            match speed.delay {
+                // let the screen read the cells between updates
+                0 => thread::yield_now(),
+                delay => thread::sleep(Duration::from_millis(delay)),
+            }
+        }
+    });
+
+    // screen drawing loop
+    loop {
+        // check if screen is closed
+        if is_quit_requested() {
+            running.store(false, Ordering::Relaxed);
+            break;
+        }
+
+        screen.check_buttons();
+        screen.check_mouse();
+        let area = screen.get_area();
+        let data;
+        let step;
+        let cells_alive;
+        let stability;
+        {
+            data = gol.read().unwrap().data_as_states(area);
+            step = gol.read().unwrap().get_steps_count();
+            cells_alive = gol.read().unwrap().count_alive_cells() as u32;
+            stability = gol.read().unwrap().get_stability();
+        }
+
+        // p key pressed
+        if paused.load(Ordering::Relaxed) != screen.is_paused() {
+            paused.store(screen.is_paused(), Ordering::Relaxed);
+        }
+
+        // + - keys pressed
+        if speed.load(Ordering::Relaxed) != screen.get_speed_level() {
+            speed.store(screen.get_speed_level(), Ordering::Relaxed);
+        }
+
+        // N key pressed
+        if screen.is_single_step() {
+            paused.store(true, Ordering::Relaxed);
+            gol.write().unwrap().step();
+            screen.set_single_step(false);
+        }
+
+        // R key presed
+        if screen.is_reset() {
+            gol.write().unwrap().record_edit();
+            gol.write().unwrap().clear_cells();
+            screen.set_seed(None);
+            screen.set_reset(false);
+        }
+
+        // T key pressed, randomize the selection if there is one
+        if screen.is_random() {
+            gol.write().unwrap().record_edit();
+            let area = match screen.get_selection() {
+                Some(selection) => {
+                    gol.write().unwrap().clear_area(selection);
+                    selection
+                }
+                None => {
+                    gol.write().unwrap().clear_cells();
+                    RANDOMIZE_AREA
+                }
+            };
+            let seed = gol
+                .write()
+                .unwrap()
+                .randomize_area(area.0, area.1, area.2, area.3);
+            screen.set_seed(Some(seed));
+            screen.set_random(false);
+        }
+
+        // Z key pressed
+        if screen.is_undo() {
+            gol.write().unwrap().undo();
+            screen.set_undo(false);
+        }
+
+        // Y key pressed
+        if screen.is_redo() {
+            gol.write().unwrap().redo();
+            screen.set_redo(false);
+        }
+
+        // B key pressed
+        if screen.is_step_back() {
+            paused.store(true, Ordering::Relaxed);
+            gol.write().unwrap().step_back();
+            screen.set_step_back(false);
+        }
+
+        // selection transform keys pressed
+        if let Some(transform) = screen.get_transform() {
+            if let Some(selection) = screen.get_selection() {
+                let mut gol = gol.write().unwrap();
+                gol.record_edit();
+                screen.set_selection(Some(gol.transform_area(selection, transform)));
+            }
+            screen.set_transform(None);
+        }
+
+        // control C X V, delete and F keys pressed
+        if let Some(action) = screen.get_action() {
+            let mut gol = gol.write().unwrap();
+            match (action, screen.get_selection()) {
+                (SelectionAction::Copy | SelectionAction::Cut, Some(selection)) => {
+                    clipboard_set(&rle::write_pattern(&Pattern::from_area(&**gol, selection)));
+                    if action == SelectionAction::Cut {
+                        gol.record_edit();
+                        gol.clear_area(selection);
+                    }
+                }
+                (SelectionAction::Paste, _) => {
+                    match clipboard_get().map(|text| formats::parse(&text)) {
+                        Some(Ok(mut pattern)) => {
+                            // the preview is placed by its top left corner
+                            if let Some((min_x, _, _, max_y)) = pattern.get_bounding_box() {
+                                pattern.transform(Transform::Translate(-min_x, -max_y));
+                                screen.set_pasting(Some(pattern));
+                            }
+                        }
+                        Some(Err(e)) => eprintln!("can't paste the clipboard: {}", e),
+                        None => {}
+                    }
+                }
+                (SelectionAction::Clear, Some(selection)) => {
+                    gol.record_edit();
+                    gol.clear_area(selection);
+                }
+                (SelectionAction::Fill, Some(selection)) => {
+                    gol.record_edit();
+                    if let Err(e) = gol.fill_area(selection) {
+                        eprintln!("{}", e);
+                    }
+                }
+                _ => {}
+            }
+            screen.set_action(None);
+        }
+
+        // click while pasting
+        // the click that places the pattern doesn't edit the cell under the mouse
+        if let Some(position) = screen.get_paste_position() {
+            if let Some(pattern) = screen.get_pasting() {
+                let mut gol = gol.write().unwrap();
+                gol.record_edit();
+                gol.paste(pattern, position, screen.get_paste_mode());
+            }
+            screen.set_pasting(None);
+        } else {
+            // add/kill cell by clicking
+            // a whole drag with the right button is undone at once
+            if screen.is_edit_started() {
+                gol.write().unwrap().record_edit();
+            }
+            if let Some(pos) = screen.mouse_clicked_pos() {
+                gol.write().unwrap().change_cell_status(pos.0, pos.1);
+            }
+        }
+
+        screen.draw_frame(data, step, cells_alive, stability).await;
+    }
+
+    join_handle.join().unwrap();
+}
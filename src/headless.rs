@@ -0,0 +1,258 @@
+use std::fs;
+
+use game_of_life::model::formats::{self, Format, Pattern};
+use game_of_life::model::game_of_life::GameOfLife;
+use game_of_life::model::rule::Rule;
+use game_of_life::model::rule_table;
+use game_of_life::model::stability::Stability;
+use game_of_life::model::universe::{Engine, Universe};
+
+const USAGE: &str = "usage: game-of-life --headless <pattern> [options]
+
+options:
+  -g, --generations N   number of generations to run (default 0)
+  -r, --rule RULE       rule used instead of the one in the pattern file
+  -t, --rule-table FILE load a Golly .rule file, its rule is used if the pattern has none
+  -e, --engine ENGINE   simulation engine: sparse (default), tiled or hashlife,
+                        tiled and hashlife only simulate Life-like rules on the infinite plane
+  -s, --until-stable    stop before the last generation if the pattern stabilizes,
+                        only the sparse engine detects it
+  -p, --print           print the final pattern
+  -o, --output FILE     write the final pattern to a file, the format depends
+                        on the extension (.rle, .cells, .lif, .mc) and defaults to RLE";
+
+/// options of the headless runner
+#[derive(Debug, PartialEq)]
+struct Options {
+    pattern: String,
+    generations: u64,
+    rule: Option<Rule>,
+    rule_table: Option<String>,
+    engine: Engine,
+    until_stable: bool,
+    print: bool,
+    output: Option<String>,
+}
+
+/// run a simulation without opening a window
+/// loads the pattern, runs the generations and prints the results
+/// only prints the usage when called with --help
+pub fn run(args: &[String]) -> Result<(), String> {
+    let Some(options) = parse_args(args)? else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+    // the table is loaded first so the pattern can name its rule
+    let table_rule = options
+        .rule_table
+        .as_deref()
+        .map(rule_table::load)
+        .transpose()?;
+
+    let input = fs::read_to_string(&options.pattern)
+        .map_err(|e| format!("can't read {}: {}", options.pattern, e))?;
+    let mut gol = GameOfLife::new();
+    let pattern = formats::load(&input, &mut gol, (0, 0))
+        .map_err(|e| format!("{}: {}", options.pattern, e))?;
+    if let (None, Some(rule)) = (&pattern.rule, table_rule) {
+        gol.set_rule(rule);
+        pattern.place(&mut gol, (0, 0));
+    }
+    if let Some(rule) = options.rule {
+        gol.set_rule(rule);
+    }
+    let mut universe = options.engine.build(gol)?;
+
+    match options.until_stable {
+        true => {
+            for _ in 0..options.generations {
+                universe.step();
+                if universe.get_stability() != Stability::Evolving {
+                    break;
+                }
+            }
+        }
+        false => universe.advance(options.generations),
+    }
+
+    println!("RULE: {}", universe.get_rule());
+    println!("STEP: {}", universe.get_steps_count());
+    println!("CELLS ALIVE: {}", universe.count_alive_cells());
+    println!("STABILITY: {}", universe.get_stability());
+    match universe.get_bounding_box() {
+        Some((min_x, min_y, max_x, max_y)) => {
+            println!("BOUNDING BOX: ({},{}) ({},{})", min_x, min_y, max_x, max_y);
+            if options.print {
+                println!("\n{}", draw(&*universe, (min_x, min_y, max_x, max_y)));
+            }
+        }
+        None => println!("BOUNDING BOX: empty"),
+    }
+
+    if let Some(output) = options.output {
+        let format = Format::from_path(&output).unwrap_or(Format::Rle);
+        let pattern = formats::write(&Pattern::from(&*universe), format)
+            .map_err(|e| format!("can't write {}: {}", output, e))?;
+        fs::write(&output, pattern).map_err(|e| format!("can't write {}: {}", output, e))?;
+    }
+    Ok(())
+}
+
+/// draw the cells of an area (min_x, min_y, max_x, max_y) of the universe in every state
+fn draw(universe: &dyn Universe, area: (i32, i32, i32, i32)) -> String {
+    let (min_x, min_y, max_x, max_y) = area;
+    let mut printed = GameOfLife::with_rule(universe.get_rule());
+    for ((x, y), state) in universe.data_as_states(area) {
+        printed.set_cell_state(x, y, state);
+    }
+    printed.data_as_str(min_x, max_x, min_y, max_y)
+}
+
+/// returns None if the usage is asked with --help
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut pattern = None;
+    let mut options = Options {
+        pattern: String::new(),
+        generations: 0,
+        rule: None,
+        rule_table: None,
+        engine: Engine::Sparse,
+        until_stable: false,
+        print: false,
+        output: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-g" | "--generations" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                options.generations = value
+                    .parse()
+                    .map_err(|_| format!("invalid number of generations '{}'", value))?;
+            }
+            "-r" | "--rule" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                options.rule = Some(Rule::parse(value).map_err(|e| e.to_string())?);
+            }
+            "-t" | "--rule-table" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                options.rule_table = Some(value.clone());
+            }
+            "-e" | "--engine" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                options.engine = value.parse()?;
+            }
+            "-s" | "--until-stable" => options.until_stable = true,
+            "-p" | "--print" => options.print = true,
+            "-o" | "--output" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                options.output = Some(value.clone());
+            }
+            "-h" | "--help" => return Ok(None),
+            arg if arg.starts_with('-') => {
+                return Err(format!("unknown option {}\n\n{}", arg, USAGE));
+            }
+            arg => {
+                if pattern.is_some() {
+                    return Err(format!("unexpected argument {}\n\n{}", arg, USAGE));
+                }
+                pattern = Some(arg.to_string());
+            }
+        }
+    }
+
+    options.pattern = pattern.ok_or(USAGE.to_string())?;
+    if options.until_stable && options.engine != Engine::Sparse {
+        return Err(format!(
+            "the {} engine doesn't detect stability, use sparse with --until-stable",
+            options.engine
+        ));
+    }
+    Ok(Some(options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = parse_args(&args(&[
+            "glider.rle",
+            "-g",
+            "100",
+            "--print",
+            "-o",
+            "out.rle",
+            "-e",
+            "hashlife",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            options,
+            Options {
+                pattern: "glider.rle".to_string(),
+                generations: 100,
+                rule: None,
+                rule_table: None,
+                engine: Engine::HashLife,
+                until_stable: false,
+                print: true,
+                output: Some("out.rle".to_string()),
+            }
+        );
+
+        let options = parse_args(&args(&[
+            "--rule",
+            "B36/S23",
+            "glider.rle",
+            "-s",
+            "-t",
+            "Langtons-Loops.rule",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(options.rule, Some(Rule::parse("B36/S23").unwrap()));
+        assert_eq!(options.engine, Engine::Sparse);
+        assert_eq!(options.rule_table.as_deref(), Some("Langtons-Loops.rule"));
+        assert!(options.until_stable);
+        assert_eq!(options.generations, 0);
+    }
+
+    #[test]
+    fn print_pattern() {
+        // a blinker that is horizontal after the first generation
+        let path = std::env::temp_dir().join("game-of-life-headless-blinker.cells");
+        fs::write(&path, "O\nO\nO\n").unwrap();
+        let pattern = path.to_str().unwrap();
+        assert_eq!(run(&args(&[pattern, "-g", "1", "-p"])), Ok(()));
+        assert_eq!(run(&args(&[pattern, "-g", "2", "-p"])), Ok(()));
+        fs::remove_file(&path).unwrap();
+
+        let mut gol = GameOfLife::with_rule(Rule::parse("/2/3").unwrap());
+        gol.set_cell_state(0, 0, 1);
+        gol.set_cell_state(1, 0, 2);
+        gol.set_cell_state(3, 0, 1);
+        gol.set_cell_state(3, -1, 1);
+        let expected = "⬜🟥⬛⬜\n⬛⬛⬛⬜\n";
+        assert_eq!(draw(&gol, (0, -1, 3, 0)), expected);
+    }
+
+    #[test]
+    fn parse_invalid_options() {
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["a.rle", "b.rle"])).is_err());
+        assert!(parse_args(&args(&["a.rle", "-g", "many"])).is_err());
+        assert!(parse_args(&args(&["a.rle", "-g"])).is_err());
+        assert!(parse_args(&args(&["a.rle", "--fast"])).is_err());
+        assert!(parse_args(&args(&["a.rle", "-e", "fast"])).is_err());
+        assert!(parse_args(&args(&["a.rle", "-s", "-e", "tiled"])).is_err());
+        assert_eq!(parse_args(&args(&["a.rle", "--help"])), Ok(None));
+    }
+}
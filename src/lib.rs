@@ -0,0 +1,3 @@
+pub mod model;
+#[cfg(feature = "gui")]
+pub mod view;
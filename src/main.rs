@@ -1,110 +1,99 @@
-pub mod model;
-pub mod view;
-
-use macroquad::prelude::*;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
-use std::thread;
-use std::time::Duration;
-
-use model::game_of_life::GameOfLife;
-use view::screen::Screen;
-
-const INITIAL_AREA: (i32, i32, i32, i32) = (-20, -20, 20, 20);
-const RANDOMIZE_AREA: (i32, i32, i32, i32) = (-20, -20, 20, 20);
-
-const SIMULATION_STEP_TIME: u64 = 100;
+#[cfg(feature = "gui")]
+mod gui;
+mod headless;
+mod search;
+
+#[cfg(feature = "gui")]
+use game_of_life::model::formats::{self, Pattern};
+#[cfg(feature = "gui")]
+use game_of_life::model::rule_table;
+#[cfg(feature = "gui")]
+use game_of_life::model::universe::Engine;
 
 /// main function
-/// connects the game logic with the view
-/// could be in a separate file
-/// but for the scope of this project, is ok to have it here
-#[macroquad::main("Conway's game of life")]
-async fn main() {
-    // use this to avoid quit when the windows is closed
-    prevent_quit();
-
-    let mut screen = Screen::new();
-
-    screen.set_area(
-        INITIAL_AREA.0,
-        INITIAL_AREA.1,
-        INITIAL_AREA.2,
-        INITIAL_AREA.3,
-    );
-
-    let gol = Arc::new(RwLock::new(GameOfLife::new()));
-    let paused = Arc::new(AtomicBool::new(false));
-    let running = Arc::new(AtomicBool::new(true));
-
-    //clone the arcs for the new thread
-    let gol_clone = gol.clone();
-    let paused_clone = paused.clone();
-    let running_clone = running.clone();
-
-    //Spawn the simulation in a new thread for better performace
-    let join_handle = thread::spawn(move || {
-        //simulation loop
-        loop {
-            if !running_clone.load(Ordering::Relaxed) {
-                break;
-            }
-            if !paused_clone.load(Ordering::Relaxed) {
-                gol_clone.write().unwrap().step();
-            }
-            thread::sleep(Duration::from_millis(SIMULATION_STEP_TIME));
-        }
-    });
-
-    // screen drawing loop
-    loop {
-        // check if screen is closed
-        if is_quit_requested() {
-            running.store(false, Ordering::Relaxed);
-            break;
+/// runs the simulation without a window when called with --headless
+/// searches soups and writes a census when called with --search
+/// or when the crate is built without the gui feature
+/// otherwise opens the game window with the pattern file passed as argument
+/// the engine chosen with --engine and the rule table loaded with --rule-table
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("--search") {
+        if let Err(e) = search::run(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
-
-        screen.check_buttons();
-        let area = screen.get_area();
-        let data;
-        let step;
-        let cells_alive;
-        {
-            data = gol.read().unwrap().data_as_vec(area);
-            step = gol.read().unwrap().get_steps_count();
-            cells_alive = gol.read().unwrap().count_alive_cells() as u32;
+        return;
+    }
+    let headless = args.first().map(String::as_str) == Some("--headless");
+
+    #[cfg(feature = "gui")]
+    if !headless {
+        let (path, engine, rule_table) = parse_window_args(&args).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        });
+        // the table is loaded first so the pattern can name its rule
+        let table_rule = rule_table.map(|path| {
+            rule_table::load(path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            })
+        });
+        let mut pattern = path.map(|path| {
+            std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|input| formats::parse(&input).map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| {
+                    eprintln!("{}: {}", path, e);
+                    std::process::exit(1);
+                })
+        });
+        if let Some(rule) = table_rule {
+            let pattern = pattern.get_or_insert_with(Pattern::default);
+            pattern.rule.get_or_insert(rule);
         }
-
-        // p key pressed
-        if paused.load(Ordering::Relaxed) != screen.is_paused() {
-            paused.store(screen.is_paused(), Ordering::Relaxed);
-        }
-
-        // R key presed
-        if screen.is_reset() {
-            gol.write().unwrap().clear_cells();
-            screen.set_reset(false);
+        let rule = pattern.as_ref().and_then(|pattern| pattern.rule.clone());
+        let topology = pattern.as_ref().and_then(|pattern| pattern.topology);
+        if let Err(e) = engine.supports(&rule.unwrap_or_default(), topology.unwrap_or_default()) {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
+        macroquad::Window::new("Conway's game of life", gui::run(pattern, engine));
+        return;
+    }
 
-        // T key pressed
-        if screen.is_random() {
-            gol.write().unwrap().clear_cells();
-            gol.write().unwrap().randomize_area(
-                RANDOMIZE_AREA.0,
-                RANDOMIZE_AREA.1,
-                RANDOMIZE_AREA.2,
-                RANDOMIZE_AREA.3,
-            );
-            screen.set_random(false);
-        }
+    let headless_args = match headless {
+        true => &args[1..],
+        false => &args[..],
+    };
+    if let Err(e) = headless::run(headless_args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
 
-        // add/kill cell by clicking
-        if let Some(pos) = screen.mouse_clicked_pos() {
-            gol.write().unwrap().change_cell_status(pos.0, pos.1);
+/// read the optional pattern path, engine and rule table file of the window
+#[cfg(feature = "gui")]
+fn parse_window_args(
+    args: &[String],
+) -> Result<(Option<&String>, Engine, Option<&String>), String> {
+    let mut path = None;
+    let mut engine = Engine::default();
+    let mut rule_table = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-e" | "--engine" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                engine = value.parse()?;
+            }
+            "-t" | "--rule-table" => {
+                rule_table = Some(args.next().ok_or(format!("missing value for {}", arg))?);
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
         }
-
-        screen.draw_frame(data, step, cells_alive).await;
     }
-
-    join_handle.join().unwrap();
+    Ok((path, engine, rule_table))
 }
@@ -1 +1,14 @@
+pub mod apgcode;
+pub mod census;
+pub mod formats;
 pub mod game_of_life;
+pub mod hashlife;
+pub mod history;
+pub mod rule;
+pub mod rule_table;
+pub mod soup;
+pub mod stability;
+pub mod tiled_life;
+pub mod topology;
+pub mod transform;
+pub mod universe;
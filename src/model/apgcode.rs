@@ -0,0 +1,278 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+use super::census::{Object, ObjectKind};
+use super::game_of_life::GameOfLife;
+use super::transform::bounding_box;
+
+/// rows of cells encoded together, each column of a strip is a 5 bit digit
+const STRIP_HEIGHT: i32 = 5;
+
+/// digits of the extended Wechsler format, 0-9 and a-z
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+type Transform = fn((i32, i32)) -> (i32, i32);
+
+/// the rotations and reflections of the square
+const ORIENTATIONS: [Transform; 8] = [
+    |(x, y)| (x, y),
+    |(x, y)| (-y, x),
+    |(x, y)| (-x, -y),
+    |(x, y)| (y, -x),
+    |(x, y)| (-x, y),
+    |(x, y)| (x, -y),
+    |(x, y)| (y, x),
+    |(x, y)| (-y, -x),
+];
+
+/// error found while reading an apgcode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApgcodeError {
+    /// the code doesn't start with xs, xp or xq followed by a number and _
+    InvalidPrefix(String),
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for ApgcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApgcodeError::InvalidPrefix(prefix) => write!(f, "invalid apgcode prefix '{}'", prefix),
+            ApgcodeError::InvalidCharacter(c) => write!(f, "invalid character '{}'", c),
+        }
+    }
+}
+
+impl Error for ApgcodeError {}
+
+/// name a finite pattern with its apgcode, like xs4_33 for the block
+/// the pattern is run alone until it repeats to find its period and displacement
+/// returns None if it dies or doesn't repeat in the max generations
+pub fn apgcode(gol: &GameOfLife, max_generations: u64) -> Option<String> {
+    let cells: Vec<(i32, i32)> = gol.get_alive_cells().iter().cloned().collect();
+    Object::classify(&cells, gol.get_rule(), max_generations)
+        .filter(|object| object.kind != ObjectKind::Unknown)
+        .map(|object| object.get_code())
+}
+
+/// the phase and orientation of a pattern with the shortest code,
+/// the lexicographically smallest if several are as short
+/// the cells are moved so the min corner is on the origin
+pub fn canonical_cells(phases: &[Vec<(i32, i32)>]) -> Vec<(i32, i32)> {
+    let mut best: Option<(String, Vec<(i32, i32)>)> = None;
+    for phase in phases {
+        for transform in ORIENTATIONS {
+            let cells = normalize(phase.iter().map(|&cell| transform(cell)).collect());
+            let code = encode_wechsler(&cells);
+            let is_better = best
+                .as_ref()
+                .is_none_or(|(best, _)| (code.len(), code.as_str()) < (best.len(), best.as_str()));
+            if is_better {
+                best = Some((code, cells));
+            }
+        }
+    }
+    best.map(|(_, cells)| cells).unwrap_or_default()
+}
+
+/// encode cells in the extended Wechsler format used after the _ of apgcodes
+/// the rows are split in strips of 5 from the top, separated by z
+/// each column of a strip is a digit whose bit 0 is the top row
+/// runs of empty columns are written as w (2), x (3) or y and a digit (4 to 39)
+pub fn encode_wechsler(cells: &[(i32, i32)]) -> String {
+    let Some((min_x, min_y, max_x, max_y)) = bounding_box(cells) else {
+        return String::new();
+    };
+    let cells: HashSet<(i32, i32)> = cells.iter().cloned().collect();
+
+    let strips = (max_y - min_y) / STRIP_HEIGHT + 1;
+    let mut strip_codes = Vec::new();
+    for strip in 0..strips {
+        let columns: Vec<u8> = (min_x..=max_x)
+            .map(|x| {
+                (0..STRIP_HEIGHT)
+                    .filter(|&bit| cells.contains(&(x, max_y - strip * STRIP_HEIGHT - bit)))
+                    .fold(0, |column, bit| column | 1 << bit)
+            })
+            .collect();
+        let last = columns.iter().rposition(|&column| column != 0);
+        let columns = match last {
+            Some(last) => &columns[..=last],
+            None => &columns[..0],
+        };
+
+        let mut code = String::new();
+        let mut zeros = 0;
+        for &column in columns {
+            if column == 0 {
+                zeros += 1;
+                continue;
+            }
+            push_zeros(&mut code, zeros);
+            zeros = 0;
+            code.push(DIGITS[column as usize] as char);
+        }
+        strip_codes.push(code);
+    }
+    strip_codes.join("z")
+}
+
+/// write a run of empty columns
+fn push_zeros(code: &mut String, mut zeros: usize) {
+    while zeros >= 40 {
+        code.push_str("yz");
+        zeros -= 39;
+    }
+    match zeros {
+        0 => {}
+        1 => code.push('0'),
+        2 => code.push('w'),
+        3 => code.push('x'),
+        zeros => {
+            code.push('y');
+            code.push(DIGITS[zeros - 4] as char);
+        }
+    }
+}
+
+/// get the cells of an apgcode with the top left corner at (0, 0)
+pub fn decode(code: &str) -> Result<Vec<(i32, i32)>, ApgcodeError> {
+    let invalid_prefix = || ApgcodeError::InvalidPrefix(code.to_string());
+    let (prefix, wechsler) = code.split_once('_').ok_or_else(invalid_prefix)?;
+    let number = prefix
+        .strip_prefix("xs")
+        .or_else(|| prefix.strip_prefix("xp"))
+        .or_else(|| prefix.strip_prefix("xq"))
+        .ok_or_else(invalid_prefix)?;
+    number.parse::<u64>().map_err(|_| invalid_prefix())?;
+
+    let mut cells = Vec::new();
+    let (mut x, mut strip) = (0, 0);
+    let mut chars = wechsler.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            'z' => (x, strip) = (0, strip + 1),
+            'w' => x += 2,
+            'x' => x += 3,
+            'y' => {
+                let c = chars.next().ok_or(ApgcodeError::InvalidCharacter('y'))?;
+                x += 4 + digit(c).ok_or(ApgcodeError::InvalidCharacter(c))? as i32;
+            }
+            c => {
+                let column = digit(c)
+                    .filter(|&column| column < 32)
+                    .ok_or(ApgcodeError::InvalidCharacter(c))?;
+                for bit in 0..STRIP_HEIGHT {
+                    if column >> bit & 1 == 1 {
+                        cells.push((x, -(strip * STRIP_HEIGHT + bit)));
+                    }
+                }
+                x += 1;
+            }
+        }
+    }
+    cells.sort_unstable();
+    Ok(cells)
+}
+
+/// make the cells of an apgcode alive in the universe
+/// moving the top left corner of the pattern to the offset
+pub fn place(code: &str, gol: &mut GameOfLife, offset: (i32, i32)) -> Result<(), ApgcodeError> {
+    for (x, y) in decode(code)? {
+        gol.set_cell_status(x + offset.0, y + offset.1, true);
+    }
+    Ok(())
+}
+
+fn digit(c: char) -> Option<u32> {
+    DIGITS
+        .iter()
+        .position(|&d| d as char == c)
+        .map(|d| d as u32)
+}
+
+/// move the cells so their min corner is on the origin and sort them
+fn normalize(mut cells: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+    let min_x = cells.iter().map(|cell| cell.0).min().unwrap_or(0);
+    let min_y = cells.iter().map(|cell| cell.1).min().unwrap_or(0);
+    for cell in cells.iter_mut() {
+        *cell = (cell.0 - min_x, cell.1 - min_y);
+    }
+    cells.sort_unstable();
+    cells
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn universe(cells: &[(i32, i32)]) -> GameOfLife {
+        let mut gol = GameOfLife::new();
+        for &(x, y) in cells {
+            gol.set_cell_status(x, y, true);
+        }
+        gol
+    }
+
+    #[test]
+    fn known_codes() {
+        let block = universe(&[(5, 5), (5, 6), (6, 5), (6, 6)]);
+        assert_eq!(apgcode(&block, 100).as_deref(), Some("xs4_33"));
+        let blinker = universe(&[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(apgcode(&blinker, 100).as_deref(), Some("xp2_7"));
+        let glider = universe(&[(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]);
+        assert_eq!(apgcode(&glider, 100).as_deref(), Some("xq4_153"));
+        let beehive = universe(&[(1, 0), (2, 0), (0, -1), (3, -1), (1, -2), (2, -2)]);
+        assert_eq!(apgcode(&beehive, 100).as_deref(), Some("xs6_696"));
+        // lightweight spaceship
+        let lwss = universe(&[
+            (1, 0),
+            (4, 0),
+            (0, -1),
+            (0, -2),
+            (4, -2),
+            (0, -3),
+            (1, -3),
+            (2, -3),
+            (3, -3),
+        ]);
+        assert_eq!(apgcode(&lwss, 100).as_deref(), Some("xq4_6frc"));
+        assert_eq!(apgcode(&universe(&[(0, 0)]), 100), None);
+    }
+
+    #[test]
+    fn long_and_tall_patterns() {
+        // two blocks 50 columns apart and 10 rows apart
+        let cells = [
+            (0, 0),
+            (0, -1),
+            (1, 0),
+            (1, -1),
+            (50, -10),
+            (50, -11),
+            (51, -10),
+            (51, -11),
+        ];
+        let code = encode_wechsler(&cells);
+        assert_eq!(code, "33zzyzy733");
+        let decoded = decode(&format!("xs8_{}", code)).unwrap();
+        let mut expected = cells.to_vec();
+        expected.sort_unstable();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn decode_and_place() {
+        let mut gol = GameOfLife::new();
+        place("xq4_153", &mut gol, (10, 10)).unwrap();
+        assert_eq!(gol.count_alive_cells(), 5);
+        assert_eq!(apgcode(&gol, 100).as_deref(), Some("xq4_153"));
+
+        assert_eq!(
+            decode("ov_33"),
+            Err(ApgcodeError::InvalidPrefix("ov_33".to_string()))
+        );
+        assert_eq!(decode("xs4_3!"), Err(ApgcodeError::InvalidCharacter('!')));
+        assert_eq!(decode("xs4_w"), Ok(Vec::new()));
+    }
+}
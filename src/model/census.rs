@@ -0,0 +1,286 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
+
+use super::apgcode::{canonical_cells, encode_wechsler};
+use super::game_of_life::GameOfLife;
+use super::rule::Rule;
+use super::soup::Soup;
+use super::stability::Stability;
+
+/// cells closer than this, in both axes, belong to the same object
+const OBJECT_DISTANCE: i32 = 2;
+
+/// generations whose population is compared to decide that a soup has stabilized
+/// the soup never repeats once a spaceship escapes, but its population does
+const POPULATION_WINDOW: usize = 240;
+
+/// longest period searched in the population of a soup
+const MAX_POPULATION_PERIOD: usize = 60;
+
+/// behaviour of an object found in the ash of a soup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator {
+        period: u64,
+    },
+    Spaceship {
+        period: u64,
+    },
+    /// didn't stabilize in the given generations
+    Unknown,
+}
+
+/// an object in its canonical form, the phase and orientation with the smallest apgcode
+/// with the min corner on the origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    pub kind: ObjectKind,
+    pub cells: Vec<(i32, i32)>,
+}
+
+impl Object {
+    /// run some cells alone until they repeat and get their canonical form
+    /// returns None if they die
+    pub fn classify(cells: &[(i32, i32)], rule: Rule, max_generations: u64) -> Option<Object> {
+        let mut gol = GameOfLife::with_rule(rule);
+        for &(x, y) in cells {
+            gol.set_cell_status(x, y, true);
+        }
+        let stability = run_until_stable(&mut gol, max_generations);
+
+        let (kind, period) = match stability {
+            Stability::Extinct => return None,
+            Stability::Evolving => (ObjectKind::Unknown, 1),
+            Stability::Static => (ObjectKind::StillLife, 1),
+            Stability::Periodic { period } => (ObjectKind::Oscillator { period }, period),
+            Stability::Translating { period, .. } => (ObjectKind::Spaceship { period }, period),
+        };
+
+        let mut phases = Vec::new();
+        for phase in 0..period {
+            if phase > 0 {
+                gol.step();
+            }
+            phases.push(gol.get_alive_cells().iter().cloned().collect());
+        }
+        Some(Object {
+            kind,
+            cells: canonical_cells(&phases),
+        })
+    }
+
+    /// apgcode of the object, the kind and size or period followed by its cells
+    /// in the extended Wechsler format
+    pub fn get_code(&self) -> String {
+        let prefix = match self.kind {
+            ObjectKind::StillLife => format!("xs{}", self.cells.len()),
+            ObjectKind::Oscillator { period } => format!("xp{}", period),
+            ObjectKind::Spaceship { period } => format!("xq{}", period),
+            ObjectKind::Unknown => format!("zz{}", self.cells.len()),
+        };
+        format!("{}_{}", prefix, encode_wechsler(&self.cells))
+    }
+}
+
+/// tally of the objects found in many soups
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Census {
+    pub rule: Rule,
+    pub soups: u64,
+    pub objects: BTreeMap<String, u64>,
+}
+
+impl Census {
+    pub fn new(rule: Rule) -> Self {
+        Census {
+            rule,
+            ..Default::default()
+        }
+    }
+
+    /// run a soup until it stabilizes or reaches the max generations
+    /// and count the objects left
+    pub fn add_soup(&mut self, soup: &Soup, max_generations: u64) {
+        let mut gol = GameOfLife::with_rule(self.rule.clone());
+        gol.add_soup(soup);
+        let mut populations = VecDeque::with_capacity(POPULATION_WINDOW);
+        for _ in 0..max_generations {
+            if gol.get_stability() != Stability::Evolving || is_periodic(&populations) {
+                break;
+            }
+            gol.step();
+            if populations.len() == POPULATION_WINDOW {
+                populations.pop_front();
+            }
+            populations.push_back(gol.count_alive_cells());
+        }
+        self.add_ash(gol.get_alive_cells(), max_generations);
+        self.soups += 1;
+    }
+
+    /// separate the cells in objects and count them
+    pub fn add_ash(&mut self, cells: &HashSet<(i32, i32)>, max_generations: u64) {
+        for object in split_objects(cells) {
+            if let Some(object) = Object::classify(&object, self.rule.clone(), max_generations) {
+                *self.objects.entry(object.get_code()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// add the soups and objects of another census of the same rule
+    pub fn merge(&mut self, other: &Census) {
+        self.soups += other.soups;
+        for (code, count) in &other.objects {
+            *self.objects.entry(code.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// objects sorted from the most common, ties by name
+    pub fn get_tally(&self) -> Vec<(&str, u64)> {
+        let mut tally: Vec<(&str, u64)> = self
+            .objects
+            .iter()
+            .map(|(code, &count)| (code.as_str(), count))
+            .collect();
+        tally.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        tally
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "@RULE {}", self.rule)?;
+        writeln!(f, "@SOUPS {}", self.soups)?;
+        writeln!(f, "@OBJECTS")?;
+        for (code, count) in self.get_tally() {
+            writeln!(f, "{} {}", code, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// step until the cells repeat or the max generations are reached
+fn run_until_stable(gol: &mut GameOfLife, max_generations: u64) -> Stability {
+    for _ in 0..max_generations {
+        if gol.get_stability() != Stability::Evolving {
+            break;
+        }
+        gol.step();
+    }
+    gol.get_stability()
+}
+
+/// returns true if the window of populations is full and repeats with a short period
+fn is_periodic(populations: &VecDeque<usize>) -> bool {
+    populations.len() == POPULATION_WINDOW
+        && (1..=MAX_POPULATION_PERIOD).any(|period| {
+            (period..POPULATION_WINDOW).all(|i| populations[i] == populations[i - period])
+        })
+}
+
+/// split the cells in groups where every cell is near another one of the group
+/// the groups are sorted by their smallest cell
+pub fn split_objects(cells: &HashSet<(i32, i32)>) -> Vec<Vec<(i32, i32)>> {
+    let mut sorted: Vec<(i32, i32)> = cells.iter().cloned().collect();
+    sorted.sort_unstable();
+
+    let mut visited = HashSet::new();
+    let mut objects = Vec::new();
+    for start in sorted {
+        if !visited.insert(start) {
+            continue;
+        }
+        let mut object = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some((x, y)) = queue.pop_front() {
+            for dx in -OBJECT_DISTANCE..=OBJECT_DISTANCE {
+                for dy in -OBJECT_DISTANCE..=OBJECT_DISTANCE {
+                    let cell = (x + dx, y + dy);
+                    if cells.contains(&cell) && visited.insert(cell) {
+                        object.push(cell);
+                        queue.push_back(cell);
+                    }
+                }
+            }
+        }
+        object.sort_unstable();
+        objects.push(object);
+    }
+    objects
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_and_classify() {
+        let block = [(0, 0), (0, 1), (1, 0), (1, 1)];
+        let blinker = [(10, 0), (11, 0), (12, 0)];
+        let glider = [(1, 20), (2, 19), (0, 18), (1, 18), (2, 18)];
+        let cells: HashSet<(i32, i32)> = block
+            .iter()
+            .chain(blinker.iter())
+            .chain(glider.iter())
+            .cloned()
+            .collect();
+        let objects = split_objects(&cells);
+        assert_eq!(objects.len(), 3);
+
+        let rule = Rule::conway();
+        let block = Object::classify(&objects[0], rule.clone(), 100).unwrap();
+        assert_eq!(block.kind, ObjectKind::StillLife);
+        assert_eq!(block.get_code(), "xs4_33");
+        let glider = Object::classify(&objects[1], rule.clone(), 100).unwrap();
+        assert_eq!(glider.kind, ObjectKind::Spaceship { period: 4 });
+        assert_eq!(glider.get_code(), "xq4_153");
+        let blinker = Object::classify(&objects[2], rule.clone(), 100).unwrap();
+        assert_eq!(blinker.kind, ObjectKind::Oscillator { period: 2 });
+        assert_eq!(blinker.get_code(), "xp2_7");
+
+        // every orientation and phase gives the same name
+        let rotated: Vec<(i32, i32)> = [(1, 20), (2, 19), (0, 18), (1, 18), (2, 18)]
+            .iter()
+            .map(|&(x, y)| (-y + 5, x))
+            .collect();
+        let rotated = Object::classify(&rotated, rule, 100).unwrap();
+        assert_eq!(rotated.get_code(), glider.get_code());
+    }
+
+    #[test]
+    fn census_of_soups() {
+        let mut census = Census::new(Rule::conway());
+        census.add_ash(
+            &HashSet::from([
+                (0, 0),
+                (0, 1),
+                (1, 0),
+                (1, 1),
+                (10, 10),
+                (10, 11),
+                (11, 10),
+                (11, 11),
+            ]),
+            100,
+        );
+        assert_eq!(census.get_tally(), vec![("xs4_33", 2)]);
+
+        let soup = Soup::new(1, (0, 0, 7, 7));
+        census.add_soup(&soup, 2000);
+        census.add_soup(&soup, 2000);
+        assert_eq!(census.soups, 2);
+        // the same soup adds the same objects twice
+        let mut other = Census::new(Rule::conway());
+        other.add_soup(&soup, 2000);
+        for (code, count) in other.get_tally() {
+            let expected = count * 2 + (code == "xs4_33") as u64 * 2;
+            assert_eq!(census.objects[code], expected);
+        }
+        assert!(
+            census
+                .to_string()
+                .starts_with("@RULE B3/S23\n@SOUPS 2\n@OBJECTS\n")
+        );
+    }
+}
@@ -0,0 +1,314 @@
+pub mod life105;
+pub mod life106;
+pub mod macrocell;
+pub mod plaintext;
+pub mod rle;
+
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use super::game_of_life::GameOfLife;
+use super::rule::Rule;
+use super::topology::Topology;
+use super::transform::{Transform, bounding_box, transform_cells};
+use super::universe::Universe;
+
+/// a pattern read from or written to a file
+/// the cells use the same coords as GameOfLife, with y growing upwards
+/// patterns read from RLE and plaintext files have their top left corner at (0, 0)
+/// Life 1.05, Life 1.06 and macrocell files keep the positions written in the file
+/// cells are the alive cells and states the cells in the other states than dead and alive,
+/// of Generations rules and rule tables, only kept by RLE files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    pub rule: Option<Rule>,
+    pub topology: Option<Topology>,
+    pub cells: Vec<(i32, i32)>,
+    pub states: Vec<((i32, i32), u8)>,
+}
+
+impl Pattern {
+    /// make the cells of the pattern alive in the universe
+    /// moving the top left corner of the pattern to the offset
+    pub fn place(&self, gol: &mut GameOfLife, offset: (i32, i32)) {
+        for &(x, y) in &self.cells {
+            gol.set_cell_status(x + offset.0, y + offset.1, true);
+        }
+        for &((x, y), state) in &self.states {
+            gol.set_cell_state(x + offset.0, y + offset.1, state);
+        }
+    }
+
+    /// set the rule and topology of the pattern in the universe, if it has them,
+    /// and place its cells moving the top left corner of the pattern to the offset
+    pub fn load_into(&self, gol: &mut GameOfLife, offset: (i32, i32)) {
+        if let Some(rule) = &self.rule {
+            gol.set_rule(rule.clone());
+        }
+        if let Some(topology) = self.topology {
+            gol.set_topology(topology);
+        }
+        self.place(gol, offset);
+    }
+
+    /// copy the cells of an area (min_x, min_y, max_x, max_y) of a universe
+    /// moving the top left corner of the area to (0, 0)
+    pub fn from_area<U: Universe + ?Sized>(universe: &U, area: (i32, i32, i32, i32)) -> Self {
+        let (min_x, _, _, max_y) = area;
+        let mut pattern = Pattern {
+            rule: Some(universe.get_rule()),
+            topology: Some(universe.get_topology()),
+            ..Default::default()
+        };
+        for ((x, y), state) in universe.data_as_states(area) {
+            pattern.add_cell((x - min_x, y - max_y), state);
+        }
+        pattern.sort();
+        pattern
+    }
+
+    /// add a cell to the alive cells or the cells in other states
+    fn add_cell(&mut self, cell: (i32, i32), state: u8) {
+        match state {
+            0 => {}
+            1 => self.cells.push(cell),
+            state => self.states.push((cell, state)),
+        }
+    }
+
+    fn sort(&mut self) {
+        self.cells.sort_unstable();
+        self.states.sort_unstable();
+    }
+
+    /// rotate, reflect or move the cells, rotations keep the center of the pattern
+    pub fn transform(&mut self, transform: Transform) {
+        if let Some(area) = self.get_bounding_box() {
+            self.cells = transform_cells(&self.cells, area, transform);
+            let positions: Vec<(i32, i32)> = self.states.iter().map(|&(cell, _)| cell).collect();
+            let moved = transform_cells(&positions, area, transform);
+            for (state, cell) in self.states.iter_mut().zip(moved) {
+                state.0 = cell;
+            }
+            self.sort();
+        }
+    }
+
+    /// get the smallest area (min_x, min_y, max_x, max_y) containing every cell
+    pub fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)> {
+        bounding_box(
+            self.cells
+                .iter()
+                .chain(self.states.iter().map(|(cell, _)| cell)),
+        )
+    }
+}
+
+impl<U: Universe + ?Sized> From<&U> for Pattern {
+    fn from(universe: &U) -> Self {
+        let mut pattern = Pattern {
+            rule: Some(universe.get_rule()),
+            topology: Some(universe.get_topology()),
+            ..Default::default()
+        };
+        if let Some(area) = universe.get_bounding_box() {
+            for (cell, state) in universe.data_as_states(area) {
+                pattern.add_cell(cell, state);
+            }
+        }
+        pattern.sort();
+        pattern
+    }
+}
+
+/// supported pattern file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Rle,
+    Plaintext,
+    Life105,
+    Life106,
+    Macrocell,
+}
+
+impl Format {
+    /// guess the format of a pattern file from its content
+    /// defaults to RLE when nothing else matches
+    pub fn detect(input: &str) -> Format {
+        let mut lines = input.lines().map(str::trim).filter(|line| !line.is_empty());
+        let Some(first) = lines.next() else {
+            return Format::Rle;
+        };
+        if first.starts_with(life105::HEADER) {
+            return Format::Life105;
+        }
+        if first.starts_with(life106::HEADER) {
+            return Format::Life106;
+        }
+        if first.starts_with(macrocell::HEADER) {
+            return Format::Macrocell;
+        }
+        if first.starts_with('!') {
+            return Format::Plaintext;
+        }
+        if first.starts_with('#') || first.starts_with('x') {
+            return Format::Rle;
+        }
+        match first.chars().all(plaintext::is_cell) {
+            true => Format::Plaintext,
+            false => Format::Rle,
+        }
+    }
+
+    /// get the format from the extension of a file
+    /// .rle, .cells, .lif, .life and .mc are recognized, .lif files are written as Life 1.06
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Format> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "rle" => Some(Format::Rle),
+            "cells" => Some(Format::Plaintext),
+            "lif" | "life" => Some(Format::Life106),
+            "mc" => Some(Format::Macrocell),
+            _ => None,
+        }
+    }
+}
+
+/// parse a pattern detecting the format from the content
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    match Format::detect(input) {
+        Format::Rle => rle::parse(input),
+        Format::Plaintext => plaintext::parse(input),
+        Format::Life105 => life105::parse(input),
+        Format::Life106 => life106::parse(input),
+        Format::Macrocell => macrocell::parse(input),
+    }
+}
+
+/// parse a pattern in any format and load it in the universe
+/// sets the rule and topology of the pattern and moves its cells by the offset
+pub fn load(input: &str, gol: &mut GameOfLife, offset: (i32, i32)) -> Result<Pattern, ParseError> {
+    match Format::detect(input) {
+        Format::Rle => rle::load(input, gol, offset),
+        Format::Plaintext => plaintext::load(input, gol, offset),
+        Format::Life105 => life105::load(input, gol, offset),
+        Format::Life106 => life106::load(input, gol, offset),
+        Format::Macrocell => macrocell::load(input, gol, offset),
+    }
+}
+
+/// serialize a pattern in the given format
+/// fails if the format can't keep the cells of the pattern
+pub fn write(pattern: &Pattern, format: Format) -> Result<String, String> {
+    match format {
+        Format::Rle => Ok(rle::write_pattern(pattern)),
+        Format::Plaintext => Ok(plaintext::write_pattern(pattern)),
+        Format::Life105 => Ok(life105::write_pattern(pattern)),
+        Format::Life106 => Ok(life106::write_pattern(pattern)),
+        Format::Macrocell => macrocell::write_pattern(pattern),
+    }
+}
+
+/// error found while reading a pattern file
+/// line starts counting from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_format() {
+        assert_eq!(
+            Format::detect("#N Glider\nx = 3, y = 3\nbo$2bo$3o!"),
+            Format::Rle
+        );
+        assert_eq!(Format::detect("x = 3, y = 3\nbo$2bo$3o!"), Format::Rle);
+        assert_eq!(
+            Format::detect("!Name: Glider\n.O.\n..O\nOOO\n"),
+            Format::Plaintext
+        );
+        assert_eq!(Format::detect("\n.O.\n..O\nOOO\n"), Format::Plaintext);
+        assert_eq!(Format::detect(".*.\n..*\n***\n"), Format::Plaintext);
+        assert_eq!(Format::detect("oo\noo\n"), Format::Plaintext);
+        assert_eq!(Format::detect("#Life 1.05\n#P 0 0\n*\n"), Format::Life105);
+        assert_eq!(Format::detect("#Life 1.06\n0 0\n"), Format::Life106);
+        assert_eq!(Format::detect("[M2] (golly 4.2)\n*\n"), Format::Macrocell);
+        assert_eq!(Format::from_path("glider.cells"), Some(Format::Plaintext));
+        assert_eq!(Format::from_path("dir/glider.RLE"), Some(Format::Rle));
+        assert_eq!(Format::from_path("glider.txt"), None);
+    }
+
+    #[test]
+    fn same_pattern_in_every_format() {
+        let inputs = [
+            "x = 3, y = 3\nbo$2bo$3o!",
+            ".O.\n..O\nOOO\n",
+            "#Life 1.05\n#P 0 0\n.*\n..*\n***\n",
+            "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n",
+        ];
+        for input in inputs {
+            let mut cells = parse(input).unwrap().cells;
+            cells.sort_unstable();
+            assert_eq!(cells, vec![(0, -2), (1, -2), (1, 0), (2, -2), (2, -1)]);
+        }
+
+        let pattern = parse(inputs[0]).unwrap();
+        for format in [
+            Format::Rle,
+            Format::Plaintext,
+            Format::Life105,
+            Format::Life106,
+            Format::Macrocell,
+        ] {
+            let mut cells = parse(&write(&pattern, format).unwrap()).unwrap().cells;
+            cells.sort_unstable();
+            let mut expected = pattern.cells.clone();
+            expected.sort_unstable();
+            assert_eq!(cells, expected);
+        }
+    }
+
+    #[test]
+    fn load_every_format() {
+        let inputs = [
+            "x = 1, y = 1, rule = B36/S23\no!",
+            "#Life 1.05\n#R 23/36\n#P 0 0\n*\n",
+            "[M2]\n#R B36/S23\n*\n",
+        ];
+        for input in inputs {
+            let mut gol = GameOfLife::new();
+            let pattern = load(input, &mut gol, (5, 5)).unwrap();
+            assert_eq!(pattern.rule, Some(Rule::parse("B36/S23").unwrap()));
+            assert_eq!(gol.get_rule(), Rule::parse("B36/S23").unwrap());
+            assert_eq!(gol.count_alive_cells(), 1);
+        }
+        let mut gol = GameOfLife::new();
+        assert!(load("#Life 1.06\n0 -2147483648\n", &mut gol, (0, 0)).is_err());
+    }
+}
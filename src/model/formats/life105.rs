@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+
+use super::super::game_of_life::GameOfLife;
+use super::super::rule::Rule;
+use super::{ParseError, Pattern};
+
+/// first line of a Life 1.05 file
+pub const HEADER: &str = "#Life 1.05";
+
+/// parse a pattern in the Life 1.05 format
+/// the cells are in blocks starting at the position given by #P
+/// with the y axis growing downwards, the positions are kept
+///
+/// #Life 1.05
+/// #D Glider
+/// #N
+/// #P -1 -1
+/// .*.
+/// ..*
+/// ***
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    let mut pattern = Pattern::default();
+    // top left corner of the current block and the next row
+    let mut block: Option<(i32, i32)> = None;
+    let mut row = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+
+        if line.starts_with("#Life") {
+            if line != HEADER {
+                return Err(ParseError::new(
+                    number,
+                    format!("unsupported version '{}'", line),
+                ));
+            }
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let tag = chars.next();
+            let text = chars.as_str().trim();
+            match tag {
+                Some('D') | Some('C') => pattern.comments.push(text.to_string()),
+                Some('N') => pattern.rule = Some(Rule::conway()),
+                Some('R') => pattern.rule = Some(parse_rule(text, number)?),
+                Some('P') => {
+                    let position: Vec<i32> = text
+                        .split_whitespace()
+                        .map(|n| n.parse::<i32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| {
+                            ParseError::new(number, format!("invalid position '{}'", text))
+                        })?;
+                    let [x, y] = position[..] else {
+                        return Err(ParseError::new(
+                            number,
+                            format!("invalid position '{}'", text),
+                        ));
+                    };
+                    block = Some((x, y));
+                    row = 0;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (block_x, block_y) = block.ok_or_else(|| ParseError::new(number, "cells before #P"))?;
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                '*' => {
+                    let x = i32::try_from(x).ok().and_then(|x| block_x.checked_add(x));
+                    let y = block_y.checked_add(row).and_then(i32::checked_neg);
+                    let (Some(x), Some(y)) = (x, y) else {
+                        return Err(ParseError::new(number, "coordinate out of range"));
+                    };
+                    pattern.cells.push((x, y));
+                }
+                c => {
+                    return Err(ParseError::new(
+                        number,
+                        format!("unexpected character '{}'", c),
+                    ));
+                }
+            }
+        }
+        row += 1;
+    }
+
+    Ok(pattern)
+}
+
+/// parse a Life 1.05 pattern and place it in the universe moving its cells by the offset
+/// the rule of the universe is changed if the pattern has one
+pub fn load(input: &str, gol: &mut GameOfLife, offset: (i32, i32)) -> Result<Pattern, ParseError> {
+    let pattern = parse(input)?;
+    pattern.load_into(gol, offset);
+    Ok(pattern)
+}
+
+/// serialize the alive cells of the universe in the Life 1.05 format
+pub fn write(gol: &GameOfLife) -> String {
+    write_pattern(&Pattern::from(gol))
+}
+
+/// serialize a pattern in the Life 1.05 format in a single block
+pub fn write_pattern(pattern: &Pattern) -> String {
+    let mut output = format!("{}\n", HEADER);
+    if let Some(name) = &pattern.name {
+        output.push_str(&format!("#D {}\n", name));
+    }
+    for comment in &pattern.comments {
+        output.push_str(&format!("#D {}\n", comment));
+    }
+    match &pattern.rule {
+        Some(rule) if !rule.is_life_like() => output.push_str(&format!("#R {}\n", rule)),
+        Some(rule) if *rule != Rule::conway() => {
+            let survival: String = (0..=8)
+                .filter(|&n| rule.survives(n))
+                .map(|n| n.to_string())
+                .collect();
+            let birth: String = (0..=8)
+                .filter(|&n| rule.is_born(n))
+                .map(|n| n.to_string())
+                .collect();
+            output.push_str(&format!("#R {}/{}\n", survival, birth));
+        }
+        _ => output.push_str("#N\n"),
+    }
+
+    let Some((min_x, min_y, max_x, max_y)) = pattern.get_bounding_box() else {
+        return output;
+    };
+    output.push_str(&format!("#P {} {}\n", min_x, -max_y));
+    let cells: HashSet<(i32, i32)> = pattern.cells.iter().cloned().collect();
+    for y in (min_y..=max_y).rev() {
+        let line: String = (min_x..=max_x)
+            .map(|x| match cells.contains(&(x, y)) {
+                true => '*',
+                false => '.',
+            })
+            .collect();
+        output.push_str(line.trim_end_matches('.'));
+        output.push('\n');
+    }
+    output
+}
+
+fn parse_rule(text: &str, number: usize) -> Result<Rule, ParseError> {
+    Rule::parse(text).map_err(|e| ParseError::new(number, e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_blocks() {
+        let pattern =
+            parse("#Life 1.05\n#D Two blocks\n#R 23/36\n#P -1 -1\n**\n**\n#P 10 0\n.*\n").unwrap();
+        assert_eq!(pattern.comments, vec!["Two blocks".to_string()]);
+        assert_eq!(pattern.rule, Some(Rule::parse("B36/S23").unwrap()));
+        assert_eq!(
+            pattern.cells,
+            vec![(-1, 1), (0, 1), (-1, 0), (0, 0), (11, 0)]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("#Life 1.05\n**\n"),
+            Err(ParseError::new(2, "cells before #P"))
+        );
+        assert_eq!(
+            parse("#Life 1.05\n#P 1\n"),
+            Err(ParseError::new(2, "invalid position '1'"))
+        );
+        assert_eq!(
+            parse("#Life 1.04\n"),
+            Err(ParseError::new(1, "unsupported version '#Life 1.04'"))
+        );
+        assert_eq!(
+            parse("#Life 1.05\n#P 0 -2147483648\n*\n"),
+            Err(ParseError::new(3, "coordinate out of range"))
+        );
+        assert_eq!(
+            parse("#Life 1.05\n#P 2147483647 0\n.*\n"),
+            Err(ParseError::new(3, "coordinate out of range"))
+        );
+        assert_eq!(
+            parse("#Life 1.05\n#P 0 2147483647\n*\n*\n"),
+            Err(ParseError::new(4, "coordinate out of range"))
+        );
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let mut gol = GameOfLife::with_rule(Rule::parse("B36/S23").unwrap());
+        for (x, y) in [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)] {
+            gol.set_cell_status(x, y, true);
+        }
+        let output = write(&gol);
+        assert_eq!(output, "#Life 1.05\n#R 23/36\n#P 0 0\n.*\n..*\n***\n");
+        let pattern = parse(&output).unwrap();
+        let cells: HashSet<(i32, i32)> = pattern.cells.into_iter().collect();
+        assert_eq!(&cells, gol.get_alive_cells());
+    }
+}
@@ -0,0 +1,120 @@
+use super::super::game_of_life::GameOfLife;
+use super::{ParseError, Pattern};
+
+/// first line of a Life 1.06 file
+pub const HEADER: &str = "#Life 1.06";
+
+/// parse a pattern in the Life 1.06 format
+/// each line has the position of an alive cell with the y axis growing downwards
+/// the positions are kept
+///
+/// #Life 1.06
+/// 0 -1
+/// 1 0
+/// -1 1
+/// 0 1
+/// 1 1
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    let mut pattern = Pattern::default();
+
+    for (i, line) in input.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+
+        if line.starts_with("#Life") {
+            if line != HEADER {
+                return Err(ParseError::new(
+                    number,
+                    format!("unsupported version '{}'", line),
+                ));
+            }
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            pattern
+                .comments
+                .push(comment.trim_start_matches(['D', 'C']).trim().to_string());
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let position: Vec<i32> = line
+            .split_whitespace()
+            .map(|n| n.parse::<i32>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| ParseError::new(number, format!("invalid position '{}'", line)))?;
+        let [x, y] = position[..] else {
+            return Err(ParseError::new(
+                number,
+                format!("invalid position '{}'", line),
+            ));
+        };
+        let y = y
+            .checked_neg()
+            .ok_or_else(|| ParseError::new(number, "coordinate out of range"))?;
+        pattern.cells.push((x, y));
+    }
+
+    Ok(pattern)
+}
+
+/// parse a Life 1.06 pattern and place it in the universe moving its cells by the offset
+pub fn load(input: &str, gol: &mut GameOfLife, offset: (i32, i32)) -> Result<Pattern, ParseError> {
+    let pattern = parse(input)?;
+    pattern.load_into(gol, offset);
+    Ok(pattern)
+}
+
+/// serialize the alive cells of the universe in the Life 1.06 format
+pub fn write(gol: &GameOfLife) -> String {
+    write_pattern(&Pattern::from(gol))
+}
+
+/// serialize a pattern in the Life 1.06 format
+/// the rule and topology are not part of the format and are lost
+pub fn write_pattern(pattern: &Pattern) -> String {
+    let mut output = format!("{}\n", HEADER);
+    let mut cells = pattern.cells.clone();
+    // top to bottom, left to right
+    cells.sort_unstable_by_key(|&(x, y)| (-y, x));
+    for (x, y) in cells {
+        output.push_str(&format!("{} {}\n", x, -y));
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_cells() {
+        let pattern = parse("#Life 1.06\n0 -1\n1 0\n\n-1 1\n").unwrap();
+        assert_eq!(pattern.cells, vec![(0, 1), (1, 0), (-1, -1)]);
+        assert_eq!(
+            parse("#Life 1.06\n0 -1\n1\n"),
+            Err(ParseError::new(3, "invalid position '1'"))
+        );
+        assert_eq!(
+            parse("#Life 1.06\n0 a\n"),
+            Err(ParseError::new(2, "invalid position '0 a'"))
+        );
+        assert_eq!(
+            parse("#Life 1.06\n0 -2147483648\n"),
+            Err(ParseError::new(2, "coordinate out of range"))
+        );
+        let pattern = parse("#Life 1.06\n-2147483648 2147483647\n").unwrap();
+        assert_eq!(pattern.cells, vec![(i32::MIN, -i32::MAX)]);
+    }
+
+    #[test]
+    fn write_cells() {
+        let mut gol = GameOfLife::new();
+        for (x, y) in [(0, 1), (1, 0), (-1, -1)] {
+            gol.set_cell_status(x, y, true);
+        }
+        assert_eq!(write(&gol), "#Life 1.06\n0 -1\n1 0\n-1 1\n");
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+
+use super::super::game_of_life::GameOfLife;
+use super::super::hashlife::{DEAD, HashLife, MAX_LEVEL, NodeId};
+use super::super::rule::{Rule, RuleError};
+use super::super::topology::Topology;
+use super::{ParseError, Pattern};
+
+/// first line of a macrocell file
+pub const HEADER: &str = "[M2]";
+
+/// patterns with more alive cells are kept in a HashLife universe when loaded
+pub const MAX_EXPANDED_CELLS: usize = 1 << 22;
+
+/// level of the nodes written as 8x8 squares of cells
+const LEAF_LEVEL: u8 = 3;
+
+/// biggest node whose cells fit in the coords of GameOfLife
+const MAX_EXPANDED_LEVEL: u8 = 32;
+
+/// parse a macrocell file into a HashLife universe
+/// each line after the header is a node, numbered from 1, that is either
+/// a 8x8 square like "..*$...*$.***$" or "level nw ne sw se"
+/// where the children are previous nodes or 0 for an empty one
+/// the last node is the root and its center is placed on the origin
+///
+/// [M2] (golly 4.2)
+/// #R B3/S23
+/// .*$..*$***$
+/// 4 0 0 0 1
+///
+/// fails if the rule isn't Life-like since HashLife can't simulate it
+pub fn parse_hashlife(input: &str) -> Result<HashLife, ParseError> {
+    let (hashlife, pattern) = read(input)?;
+    simulated(input, hashlife, &pattern)
+}
+
+/// parse a macrocell file expanding its cells into a pattern
+/// fails if the pattern has too many alive cells
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    let (hashlife, mut pattern) = read(input)?;
+    if !is_expandable(&hashlife) {
+        return Err(ParseError::new(
+            root_line(input),
+            format!("pattern too large, {} cells", hashlife.count_alive_cells()),
+        ));
+    }
+    pattern.cells = expand(&hashlife);
+    Ok(pattern)
+}
+
+/// parse a macrocell file and place it in the universe moving its cells by the offset
+/// the rule and topology of the universe are changed if the pattern has them
+/// fails like parse if the pattern is too large, parse_hashlife can read it instead
+pub fn load(input: &str, gol: &mut GameOfLife, offset: (i32, i32)) -> Result<Pattern, ParseError> {
+    let pattern = parse(input)?;
+    pattern.load_into(gol, offset);
+    Ok(pattern)
+}
+
+/// parse a macrocell file and load it in the universe like load
+/// when the pattern is too large to expand the universe isn't changed
+/// and the HashLife universe is returned instead
+pub fn load_or_hashlife(
+    input: &str,
+    gol: &mut GameOfLife,
+    offset: (i32, i32),
+) -> Result<Option<HashLife>, ParseError> {
+    let (hashlife, mut pattern) = read(input)?;
+    if !is_expandable(&hashlife) {
+        return simulated(input, hashlife, &pattern).map(Some);
+    }
+    pattern.cells = expand(&hashlife);
+    pattern.load_into(gol, offset);
+    Ok(None)
+}
+
+/// serialize the alive cells of the universe as a macrocell
+/// fails if there are cells in other states than dead and alive
+pub fn write(gol: &GameOfLife) -> Result<String, String> {
+    write_pattern(&Pattern::from(gol))
+}
+
+/// serialize a pattern as a macrocell
+/// fails if there are cells in other states than dead and alive,
+/// since the 8x8 squares of the nodes only have dead and alive cells
+pub fn write_pattern(pattern: &Pattern) -> Result<String, String> {
+    if let Some(&((x, y), state)) = pattern.states.first() {
+        return Err(format!(
+            "macrocell files only keep dead and alive cells, ({}, {}) is in state {}",
+            x, y, state
+        ));
+    }
+    // the universe only stores the cells, so it keeps its default rule
+    let mut hashlife = HashLife::new();
+    for &(x, y) in &pattern.cells {
+        hashlife.set_cell(x, y, true);
+    }
+    Ok(write_nodes(
+        &hashlife,
+        &pattern.rule.clone().unwrap_or_default(),
+        &pattern.comments,
+        pattern.topology.unwrap_or_default(),
+    ))
+}
+
+/// serialize a HashLife universe as a macrocell
+/// equal nodes are written only once
+pub fn write_hashlife(hashlife: &HashLife) -> String {
+    write_nodes(hashlife, &hashlife.get_rule(), &[], Topology::Infinite)
+}
+
+fn write_nodes(
+    hashlife: &HashLife,
+    rule: &Rule,
+    comments: &[String],
+    topology: Topology,
+) -> String {
+    let mut output = format!("{}\n", HEADER);
+    let mut rule = rule.to_string();
+    if topology != Topology::Infinite {
+        rule.push_str(&format!(":{}", topology));
+    }
+    output.push_str(&format!("#R {}\n", rule));
+    for comment in comments {
+        output.push_str(&format!("#C {}\n", comment));
+    }
+
+    let mut numbers = HashMap::new();
+    write_node(hashlife, hashlife.get_root(), &mut numbers, &mut output);
+    output
+}
+
+/// write the children of a node before it and get its number, 0 if empty
+fn write_node(
+    hashlife: &HashLife,
+    id: NodeId,
+    numbers: &mut HashMap<NodeId, usize>,
+    output: &mut String,
+) -> usize {
+    let node = hashlife.get_node(id);
+    if node.population == 0 {
+        return 0;
+    }
+    if let Some(&number) = numbers.get(&id) {
+        return number;
+    }
+
+    if node.level == LEAF_LEVEL {
+        let rows: Vec<String> = (0..8)
+            .rev()
+            .map(|y| {
+                let row: String = (0..8)
+                    .map(|x| match hashlife.get_cell_in(id, x, y) {
+                        true => '*',
+                        false => '.',
+                    })
+                    .collect();
+                row.trim_end_matches('.').to_string()
+            })
+            .collect();
+        let last = rows.iter().rposition(|row| !row.is_empty()).unwrap_or(0);
+        for row in &rows[..=last] {
+            output.push_str(row);
+            output.push('$');
+        }
+    } else {
+        let children = [node.nw, node.ne, node.sw, node.se]
+            .map(|child| write_node(hashlife, child, numbers, output));
+        output.push_str(&format!(
+            "{} {} {} {} {}",
+            node.level, children[0], children[1], children[2], children[3]
+        ));
+    }
+    output.push('\n');
+
+    let number = numbers.len() + 1;
+    numbers.insert(id, number);
+    number
+}
+
+/// read the universe and the rest of the information of the pattern
+fn read(input: &str) -> Result<(HashLife, Pattern), ParseError> {
+    let mut pattern = Pattern::default();
+    let mut hashlife = HashLife::new();
+    // node ids and levels by number in the file
+    let mut nodes: Vec<(NodeId, u8)> = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+
+        if number == 1 {
+            if !line.starts_with(HEADER) {
+                return Err(ParseError::new(number, "missing [M2] header"));
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let tag = chars.next();
+            let text = chars.as_str().trim();
+            match tag {
+                Some('R') => {
+                    let error = |e: RuleError| ParseError::new(number, e.to_string());
+                    let (rulestring, suffix) = match text.split_once(':') {
+                        Some((rulestring, suffix)) => (rulestring, Some(suffix)),
+                        None => (text, None),
+                    };
+                    let rule = Rule::parse(rulestring).map_err(error)?;
+                    // HashLife only simulates Life-like rules, the others are kept in the pattern
+                    if rule.is_life_like() {
+                        hashlife
+                            .set_rule(rule.clone())
+                            .map_err(|e| ParseError::new(number, e))?;
+                    }
+                    pattern.rule = Some(rule);
+                    pattern.topology = suffix
+                        .map(Topology::parse)
+                        .transpose()
+                        .map_err(|e| ParseError::new(number, e.to_string()))?;
+                }
+                Some('C') | Some('D') => pattern.comments.push(text.to_string()),
+                _ => {}
+            }
+            continue;
+        }
+
+        let node = match line.starts_with(['.', '*', '$']) {
+            true => (read_leaf(&mut hashlife, line, number)?, LEAF_LEVEL),
+            false => read_node(&mut hashlife, &nodes, line, number)?,
+        };
+        nodes.push(node);
+    }
+
+    let root = match nodes.last() {
+        Some(&(id, _)) => id,
+        None => DEAD,
+    };
+    match root {
+        DEAD => hashlife.clear_cells(),
+        root => hashlife.set_root(root),
+    }
+    Ok((hashlife, pattern))
+}
+
+/// read a 8x8 square of cells, rows are separated by $ starting from the top
+fn read_leaf(hashlife: &mut HashLife, line: &str, number: usize) -> Result<NodeId, ParseError> {
+    let mut id = hashlife.empty_node(LEAF_LEVEL);
+    let (mut x, mut y) = (0, 7);
+    for c in line.chars() {
+        match c {
+            '.' => x += 1,
+            '*' => {
+                if x > 7 || y < 0 {
+                    return Err(ParseError::new(number, "cell outside of the 8x8 square"));
+                }
+                id = hashlife.set_cell_in(id, x, y, true);
+                x += 1;
+            }
+            '$' => (x, y) = (0, y - 1),
+            c => {
+                return Err(ParseError::new(
+                    number,
+                    format!("unexpected character '{}'", c),
+                ));
+            }
+        }
+    }
+    Ok(id)
+}
+
+/// read a line like "level nw ne sw se" with the numbers of the children
+fn read_node(
+    hashlife: &mut HashLife,
+    nodes: &[(NodeId, u8)],
+    line: &str,
+    number: usize,
+) -> Result<(NodeId, u8), ParseError> {
+    let invalid = || ParseError::new(number, format!("invalid node '{}'", line));
+    let values: Vec<usize> = line
+        .split_whitespace()
+        .map(|n| n.parse::<usize>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid())?;
+    let [level, nw, ne, sw, se] = values[..] else {
+        return Err(invalid());
+    };
+    if level <= LEAF_LEVEL as usize || level > MAX_LEVEL as usize {
+        return Err(ParseError::new(number, format!("invalid level {}", level)));
+    }
+
+    let level = level as u8;
+    let mut children = [DEAD; 4];
+    for (child, n) in children.iter_mut().zip([nw, ne, sw, se]) {
+        *child = match n {
+            0 => hashlife.empty_node(level - 1),
+            n => match nodes.get(n - 1) {
+                Some(&(id, child_level)) if child_level == level - 1 => id,
+                Some(_) => {
+                    return Err(ParseError::new(
+                        number,
+                        format!("node {} has the wrong level", n),
+                    ));
+                }
+                None => {
+                    return Err(ParseError::new(number, format!("unknown node {}", n)));
+                }
+            },
+        };
+    }
+    let id = hashlife.join(children[0], children[1], children[2], children[3]);
+    Ok((id, level))
+}
+
+/// get the universe read from a file to simulate it with HashLife
+/// fails on the line of the rule if it isn't Life-like
+fn simulated(input: &str, hashlife: HashLife, pattern: &Pattern) -> Result<HashLife, ParseError> {
+    match &pattern.rule {
+        Some(rule) if !rule.is_life_like() => {
+            let number = input
+                .lines()
+                .position(|line| line.trim().starts_with("#R"))
+                .map_or(1, |i| i + 1);
+            Err(ParseError::new(
+                number,
+                format!("HashLife only simulates Life-like rules, not {}", rule),
+            ))
+        }
+        _ => Ok(hashlife),
+    }
+}
+
+/// line of the root, the last node of the file
+fn root_line(input: &str) -> usize {
+    input
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .last()
+        .map_or(1, |(i, _)| i + 1)
+}
+
+fn is_expandable(hashlife: &HashLife) -> bool {
+    hashlife.count_alive_cells() <= MAX_EXPANDED_CELLS
+        && hashlife.get_node(hashlife.get_root()).level <= MAX_EXPANDED_LEVEL
+}
+
+/// every alive cell of the universe, sorted
+fn expand(hashlife: &HashLife) -> Vec<(i32, i32)> {
+    let mut cells: Vec<(i32, i32)> = hashlife
+        .data_as_vec((i32::MIN, i32::MIN, i32::MAX, i32::MAX))
+        .into_iter()
+        .collect();
+    cells.sort_unstable();
+    cells
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_glider() {
+        let input = "[M2] (golly 4.2)\n#R B36/S23\n#C a glider\n.*$..*$***$\n4 0 0 0 1\n";
+        let pattern = parse(input).unwrap();
+        assert_eq!(pattern.rule, Some(Rule::parse("B36/S23").unwrap()));
+        assert_eq!(pattern.comments, vec!["a glider".to_string()]);
+        // the leaf is the south east quarter of a 16x16 node centered on the origin
+        assert_eq!(
+            pattern.cells,
+            vec![(0, -3), (1, -3), (1, -1), (2, -3), (2, -2)]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("x = 1, y = 1\no!").unwrap_err(),
+            ParseError::new(1, "missing [M2] header")
+        );
+        assert_eq!(
+            parse("[M2]\n*\n5 0 0 0 1\n").unwrap_err(),
+            ParseError::new(3, "node 1 has the wrong level")
+        );
+        assert_eq!(
+            parse("[M2]\n4 0 0 0 1\n").unwrap_err(),
+            ParseError::new(2, "unknown node 1")
+        );
+        assert_eq!(
+            parse("[M2]\n*********\n").unwrap_err(),
+            ParseError::new(2, "cell outside of the 8x8 square")
+        );
+    }
+
+    #[test]
+    fn too_large_patterns() {
+        // a full 8x8 square repeated until the root has 2^24 cells
+        let mut input = "[M2]\n".to_string() + &"********$".repeat(8) + "\n";
+        for level in 4..=12 {
+            let child = level - 3;
+            input.push_str(&format!(
+                "{} {} {} {} {}\n",
+                level, child, child, child, child
+            ));
+        }
+        input.push_str("\n#C the root is on line 11\n");
+        assert_eq!(
+            parse(&input).unwrap_err(),
+            ParseError::new(11, "pattern too large, 16777216 cells")
+        );
+        let mut gol = GameOfLife::new();
+        let hashlife = load_or_hashlife(&input, &mut gol, (0, 0)).unwrap();
+        assert_eq!(hashlife.map(|h| h.count_alive_cells()), Some(1 << 24));
+        assert_eq!(gol.count_alive_cells(), 0);
+    }
+
+    #[test]
+    fn multi_state_patterns_are_refused() {
+        let pattern = Pattern {
+            rule: Some(Rule::parse("/2/3").unwrap()),
+            cells: vec![(0, 0)],
+            states: vec![((1, 0), 2)],
+            ..Default::default()
+        };
+        assert!(write_pattern(&pattern).is_err());
+        let mut gol = GameOfLife::with_rule(Rule::parse("/2/3").unwrap());
+        gol.set_cell_state(0, 0, 2);
+        assert!(write(&gol).is_err());
+    }
+
+    #[test]
+    fn shared_nodes_are_written_once() {
+        let mut gol = GameOfLife::new();
+        // four blocks with the same position inside their 8x8 squares
+        for (x, y) in [(-8, -8), (-8, 0), (0, -8), (0, 0)] {
+            for (dx, dy) in [(1, 1), (1, 2), (2, 1), (2, 2)] {
+                gol.set_cell_status(x + dx, y + dy, true);
+            }
+        }
+        let output = write(&gol).unwrap();
+        assert_eq!(output, "[M2]\n#R B3/S23\n$$$$$.**$.**$\n4 1 1 1 1\n");
+
+        let mut loaded = GameOfLife::new();
+        assert!(
+            load_or_hashlife(&output, &mut loaded, (0, 0))
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(loaded.get_alive_cells(), gol.get_alive_cells());
+    }
+
+    #[test]
+    fn hashlife_round_trip() {
+        let mut hashlife = HashLife::new();
+        for (x, y) in [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2), (-300, 500)] {
+            hashlife.set_cell(x, y, true);
+        }
+        let loaded = parse_hashlife(&write_hashlife(&hashlife)).unwrap();
+        let area = (-1000, -1000, 1000, 1000);
+        assert_eq!(loaded.data_as_vec(area), hashlife.data_as_vec(area));
+        assert_eq!(parse_hashlife("[M2]\n").unwrap().count_alive_cells(), 0);
+
+        // other rules can be expanded but not simulated
+        let input = "[M2]\n#R /2/3\n*\n";
+        assert_eq!(
+            parse(input).unwrap().rule,
+            Some(Rule::parse("/2/3").unwrap())
+        );
+        assert_eq!(parse_hashlife(input).err().map(|e| e.line), Some(2));
+    }
+}
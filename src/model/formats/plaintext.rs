@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use super::super::game_of_life::GameOfLife;
+use super::{ParseError, Pattern};
+
+/// returns true if the character is a dead or alive cell of a plaintext row
+pub(super) fn is_cell(c: char) -> bool {
+    matches!(c, '.' | 'O' | 'o' | '*')
+}
+
+/// parse a pattern in the plaintext format used by the .cells files
+///
+/// !Name: Glider
+/// !comment
+/// .O.
+/// ..O
+/// OOO
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    let mut pattern = Pattern::default();
+    let mut row = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim_end();
+
+        if let Some(comment) = line.strip_prefix('!') {
+            match comment.strip_prefix("Name:") {
+                Some(name) => pattern.name = Some(name.trim().to_string()),
+                None => match comment.strip_prefix("Author:") {
+                    Some(author) => pattern.author = Some(author.trim().to_string()),
+                    None => pattern.comments.push(comment.trim().to_string()),
+                },
+            }
+            continue;
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                c if is_cell(c) => pattern.cells.push((x as i32, -row)),
+                c => {
+                    return Err(ParseError::new(
+                        number,
+                        format!("unexpected character '{}'", c),
+                    ));
+                }
+            }
+        }
+        row += 1;
+    }
+
+    Ok(pattern)
+}
+
+/// parse a plaintext pattern and place it in the universe with its top left corner
+/// in the offset
+pub fn load(input: &str, gol: &mut GameOfLife, offset: (i32, i32)) -> Result<Pattern, ParseError> {
+    let pattern = parse(input)?;
+    pattern.load_into(gol, offset);
+    Ok(pattern)
+}
+
+/// serialize the alive cells of the universe in the plaintext format
+pub fn write(gol: &GameOfLife) -> String {
+    write_pattern(&Pattern::from(gol))
+}
+
+/// serialize a pattern in the plaintext format
+/// the rule and topology are not part of the format and are lost
+pub fn write_pattern(pattern: &Pattern) -> String {
+    let mut output = String::new();
+    if let Some(name) = &pattern.name {
+        output.push_str(&format!("!Name: {}\n", name));
+    }
+    if let Some(author) = &pattern.author {
+        output.push_str(&format!("!Author: {}\n", author));
+    }
+    for comment in &pattern.comments {
+        output.push_str(&format!("!{}\n", comment));
+    }
+
+    let Some((min_x, min_y, max_x, max_y)) = pattern.get_bounding_box() else {
+        return output;
+    };
+    let cells: HashSet<(i32, i32)> = pattern.cells.iter().cloned().collect();
+    for y in (min_y..=max_y).rev() {
+        for x in min_x..=max_x {
+            output.push(match cells.contains(&(x, y)) {
+                true => 'O',
+                false => '.',
+            });
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_glider() {
+        let pattern = parse("!Name: Glider\n!The first spaceship\n.O.\n..O\nOOO\n").unwrap();
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.comments, vec!["The first spaceship".to_string()]);
+        assert_eq!(
+            pattern.cells,
+            vec![(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]
+        );
+        assert_eq!(
+            parse("!Name: x\n.O\n.X\n"),
+            Err(ParseError::new(3, "unexpected character 'X'"))
+        );
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let mut gol = GameOfLife::new();
+        load("O..O\n\n.OO.\n", &mut gol, (3, 3)).unwrap();
+        assert_eq!(write(&gol), "O..O\n....\n.OO.\n");
+    }
+}
@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+
+use super::super::game_of_life::GameOfLife;
+use super::super::rule::{Rule, RuleError};
+use super::super::topology::Topology;
+use super::{ParseError, Pattern};
+
+/// maximum length of the lines with cells written in a RLE file
+const MAX_LINE_LENGTH: usize = 70;
+
+/// most cells that aren't dead read from a RLE file
+pub const MAX_CELLS: usize = 1 << 24;
+
+/// parse a pattern in the Run Length Encoded format
+///
+/// #N name
+/// #C comment
+/// x = 3, y = 3, rule = B3/S23
+/// bo$2bo$3o!
+///
+/// patterns of rules with more states use . for dead cells and A to X for the states 1 to 24,
+/// the next states add a prefix from p to y, pA is 25
+///
+/// fails if a cell is outside of the i32 coords or if there are more than MAX_CELLS cells
+pub fn parse(input: &str) -> Result<Pattern, ParseError> {
+    let mut pattern = Pattern::default();
+    let mut header_found = false;
+
+    // position of the next cell, rows grow downwards
+    // they are wider than the coords so that runs past the edges can be reported
+    let (mut x, mut row): (i64, i64) = (0, 0);
+    let mut count: Option<i32> = None;
+    let mut prefix: Option<u32> = None;
+
+    'lines: for (i, line) in input.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        let out_of_range = || ParseError::new(number, "coordinate out of range");
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let tag = chars.next();
+            let text = chars.as_str().trim().to_string();
+            match tag {
+                Some('N') => pattern.name = Some(text),
+                Some('O') => pattern.author = Some(text),
+                Some('C') | Some('c') => pattern.comments.push(text),
+                _ => {}
+            }
+            continue;
+        }
+
+        if !header_found && line.starts_with('x') {
+            header_found = true;
+            (pattern.rule, pattern.topology) = parse_header(line, number)?;
+            continue;
+        }
+
+        for c in line.chars() {
+            match c {
+                '0'..='9' => {
+                    let digit = c.to_digit(10).unwrap() as i32;
+                    count = Some(
+                        count
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|n| n.checked_add(digit))
+                            .ok_or_else(|| ParseError::new(number, "run count too large"))?,
+                    );
+                }
+                'b' | '.' => x += count.take().unwrap_or(1) as i64,
+                '$' => {
+                    row += count.take().unwrap_or(1) as i64;
+                    x = 0;
+                }
+                '!' => break 'lines,
+                'p'..='y' if prefix.is_none() => prefix = Some(c as u32 - 'p' as u32 + 1),
+                c if c.is_ascii_alphabetic() => {
+                    let state = match c {
+                        'A'..='X' => prefix.take().unwrap_or(0) * 24 + c as u32 - 'A' as u32 + 1,
+                        _ => 1,
+                    };
+                    let state = u8::try_from(state).map_err(|_| {
+                        ParseError::new(number, format!("state {} out of range", state))
+                    })?;
+                    let count = count.take().unwrap_or(1);
+                    if pattern.cells.len() + pattern.states.len() + count as usize > MAX_CELLS {
+                        return Err(ParseError::new(
+                            number,
+                            format!("too many cells, the limit is {}", MAX_CELLS),
+                        ));
+                    }
+                    let end = x + count as i64;
+                    let y = i32::try_from(-row).map_err(|_| out_of_range())?;
+                    let (first, last) = (i32::try_from(x), i32::try_from(end - 1));
+                    let (Ok(first), Ok(last)) = (first, last) else {
+                        return Err(out_of_range());
+                    };
+                    for x in first..=last {
+                        pattern.add_cell((x, y), state);
+                    }
+                    x = end;
+                }
+                c if c.is_whitespace() => {}
+                c => {
+                    return Err(ParseError::new(
+                        number,
+                        format!("unexpected character '{}'", c),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(pattern)
+}
+
+/// parse a RLE pattern and place it in the universe with its top left corner
+/// in the offset, the rule and topology of the universe are changed
+/// if the pattern has them
+pub fn load(input: &str, gol: &mut GameOfLife, offset: (i32, i32)) -> Result<Pattern, ParseError> {
+    let pattern = parse(input)?;
+    pattern.load_into(gol, offset);
+    Ok(pattern)
+}
+
+/// serialize the alive cells of the universe as RLE
+pub fn write(gol: &GameOfLife) -> String {
+    write_pattern(&Pattern::from(gol))
+}
+
+/// serialize a pattern as RLE
+/// patterns with cells in other states than dead and alive use the tags of multi-state RLE
+pub fn write_pattern(pattern: &Pattern) -> String {
+    let mut output = String::new();
+    if let Some(name) = &pattern.name {
+        output.push_str(&format!("#N {}\n", name));
+    }
+    if let Some(author) = &pattern.author {
+        output.push_str(&format!("#O {}\n", author));
+    }
+    for comment in &pattern.comments {
+        output.push_str(&format!("#C {}\n", comment));
+    }
+
+    let (min_x, min_y, max_x, max_y) = pattern.get_bounding_box().unwrap_or((0, 0, -1, -1));
+    let mut rule = pattern.rule.clone().unwrap_or_default().to_string();
+    if let Some(topology) = pattern.topology.filter(|&t| t != Topology::Infinite) {
+        rule.push_str(&format!(":{}", topology));
+    }
+    output.push_str(&format!(
+        "x = {}, y = {}, rule = {}\n",
+        max_x - min_x + 1,
+        max_y - min_y + 1,
+        rule
+    ));
+
+    let cells: HashMap<(i32, i32), u8> = pattern
+        .cells
+        .iter()
+        .map(|&cell| (cell, 1))
+        .chain(pattern.states.iter().cloned())
+        .collect();
+    let multi_state = !pattern.states.is_empty();
+    let mut writer = RunWriter::new();
+    for y in (min_y..=max_y).rev() {
+        let mut x = min_x;
+        while x <= max_x {
+            let state = cells.get(&(x, y)).copied().unwrap_or(0);
+            let start = x;
+            while x <= max_x && cells.get(&(x, y)).copied().unwrap_or(0) == state {
+                x += 1;
+            }
+            // dead cells at the end of a row are not written
+            if state != 0 || x <= max_x {
+                writer.push(x - start, &get_tag(state, multi_state));
+            }
+        }
+        if y > min_y {
+            writer.push(1, "$");
+        }
+    }
+    writer.push(1, "!");
+    output.push_str(&writer.finish());
+    output
+}
+
+/// get the tag of a state, b and o or the letters of multi-state RLE
+fn get_tag(state: u8, multi_state: bool) -> String {
+    match (state, multi_state) {
+        (0, false) => "b".to_string(),
+        (_, false) => "o".to_string(),
+        (0, true) => ".".to_string(),
+        (state, true) => {
+            let (prefix, letter) = ((state - 1) / 24, (state - 1) % 24);
+            let letter = (b'A' + letter) as char;
+            match prefix {
+                0 => letter.to_string(),
+                prefix => format!("{}{}", (b'p' + prefix - 1) as char, letter),
+            }
+        }
+    }
+}
+
+/// read the rule from a line like "x = 3, y = 3, rule = B3/S23"
+/// the rule can end with a topology like "B3/S23:T30,20"
+fn parse_header(line: &str, number: usize) -> Result<(Option<Rule>, Option<Topology>), ParseError> {
+    let mut rule = None;
+    let mut topology = None;
+
+    // the rule is the last field and may contain commas
+    let (fields, rule_field) = match line.find("rule") {
+        Some(i) => (line[..i].trim_end().trim_end_matches(','), Some(&line[i..])),
+        None => (line, None),
+    };
+
+    for field in fields.split(',').chain(rule_field) {
+        let (key, value) = field.split_once('=').ok_or_else(|| {
+            ParseError::new(number, format!("invalid header field '{}'", field.trim()))
+        })?;
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "x" | "y" => {
+                value.parse::<u32>().map_err(|_| {
+                    ParseError::new(number, format!("invalid pattern size '{}'", value))
+                })?;
+            }
+            "rule" => {
+                let error = |e: RuleError| ParseError::new(number, e.to_string());
+                let (rulestring, suffix) = match value.split_once(':') {
+                    Some((rulestring, suffix)) => (rulestring, Some(suffix)),
+                    None => (value, None),
+                };
+                rule = Some(Rule::parse(rulestring).map_err(error)?);
+                topology = suffix
+                    .map(Topology::parse)
+                    .transpose()
+                    .map_err(|e| ParseError::new(number, e.to_string()))?;
+            }
+            _ => {}
+        }
+    }
+    Ok((rule, topology))
+}
+
+/// accumulates runs of the same tag and splits them in lines
+struct RunWriter {
+    output: String,
+    line_length: usize,
+    pending: Option<(i32, String)>,
+}
+
+impl RunWriter {
+    fn new() -> Self {
+        RunWriter {
+            output: String::new(),
+            line_length: 0,
+            pending: None,
+        }
+    }
+
+    fn push(&mut self, count: i32, tag: &str) {
+        match &mut self.pending {
+            Some((pending_count, pending_tag)) if pending_tag == tag => *pending_count += count,
+            _ => {
+                self.flush();
+                self.pending = Some((count, tag.to_string()));
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        let Some((count, tag)) = self.pending.take() else {
+            return;
+        };
+        let run = match count {
+            1 => tag,
+            _ => format!("{}{}", count, tag),
+        };
+        if self.line_length + run.len() > MAX_LINE_LENGTH {
+            self.output.push('\n');
+            self.line_length = 0;
+        }
+        self.line_length += run.len();
+        self.output.push_str(&run);
+    }
+
+    fn finish(mut self) -> String {
+        self.flush();
+        self.output.push('\n');
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GLIDER: &str = "#N Glider
+#O Richard K. Guy
+#C The smallest, most common, and first discovered spaceship.
+x = 3, y = 3, rule = B3/S23
+bob$2bo$3o!
+";
+
+    #[test]
+    fn parse_glider() {
+        let pattern = parse(GLIDER).unwrap();
+        assert_eq!(pattern.name.as_deref(), Some("Glider"));
+        assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+        assert_eq!(pattern.comments.len(), 1);
+        assert_eq!(pattern.rule, Some(Rule::conway()));
+        assert_eq!(
+            pattern.cells,
+            vec![(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]
+        );
+    }
+
+    #[test]
+    fn multi_state_cells() {
+        let input = "x = 4, y = 2, rule = Wireworld\nAB2C$2.pA!\n";
+        let pattern = parse(input).unwrap();
+        assert_eq!(pattern.rule, Rule::parse("Wireworld").ok());
+        assert_eq!(pattern.cells, vec![(0, 0)]);
+        assert_eq!(
+            pattern.states,
+            vec![((1, 0), 2), ((2, 0), 3), ((3, 0), 3), ((2, -1), 25)]
+        );
+        assert_eq!(write_pattern(&pattern), input);
+        assert!(parse("x = 1, y = 1\nyX!").is_err());
+    }
+
+    #[test]
+    fn parse_multiline_runs() {
+        let pattern = parse("x = 12, y = 3\n1\n2o$\n\n2$o!").unwrap();
+        assert_eq!(pattern.rule, None);
+        let expected: Vec<(i32, i32)> = (0..12).map(|x| (x, 0)).chain([(0, -3)]).collect();
+        assert_eq!(pattern.cells, expected);
+    }
+
+    #[test]
+    fn load_at_offset() {
+        let mut gol = GameOfLife::new();
+        load("x = 2, y = 1, rule = B36/S23\n2o!", &mut gol, (10, -5)).unwrap();
+        assert_eq!(gol.get_rule().to_string(), "B36/S23");
+        assert!(gol.get_cell_status(10, -5));
+        assert!(gol.get_cell_status(11, -5));
+        assert_eq!(gol.count_alive_cells(), 2);
+    }
+
+    #[test]
+    fn topology_in_rule() {
+        let mut gol = GameOfLife::new();
+        load("x = 1, y = 1, rule = B3/S23:T30,20\no!", &mut gol, (0, 0)).unwrap();
+        assert_eq!(
+            gol.get_topology(),
+            Topology::Torus {
+                width: 30,
+                height: 20
+            }
+        );
+        assert_eq!(write(&gol), "x = 1, y = 1, rule = B3/S23:T30,20\no!\n");
+        assert_eq!(
+            parse("x = 1, y = 1, rule = B3/S23:Q30\no!"),
+            Err(ParseError::new(1, "invalid topology 'Q30'"))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("#N test\nx = 3, y = 3, rule = B3/S2!\n3o!"),
+            Err(ParseError::new(2, "invalid neighbour count '!'"))
+        );
+        assert_eq!(
+            parse("x = 3, y = 3\nbo$\n2b?!"),
+            Err(ParseError::new(3, "unexpected character '?'"))
+        );
+        assert_eq!(
+            parse("x = three, y = 3\n3o!"),
+            Err(ParseError::new(1, "invalid pattern size 'three'"))
+        );
+        assert_eq!(
+            parse("x = 1, y = 1\n2147483647b2o!"),
+            Err(ParseError::new(2, "coordinate out of range"))
+        );
+        assert_eq!(
+            parse("x = 1, y = 1\n2147483647$o2$o!"),
+            Err(ParseError::new(2, "coordinate out of range"))
+        );
+        assert_eq!(
+            parse("x = 1, y = 1\n999999999o!"),
+            Err(ParseError::new(2, "too many cells, the limit is 16777216"))
+        );
+        let pattern = parse("x = 1, y = 1\n2147483647bo!").unwrap();
+        assert_eq!(pattern.cells, vec![(i32::MAX, 0)]);
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let mut gol = GameOfLife::new();
+        load(GLIDER, &mut gol, (0, 0)).unwrap();
+        assert_eq!(write(&gol), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+
+        let mut gol = GameOfLife::new();
+        for x in 0..100 {
+            gol.set_cell_status(x * 2, 0, true);
+        }
+        gol.set_cell_status(0, -4, true);
+        let rle = write(&gol);
+        assert!(rle.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert!(rle.replace('\n', "").ends_with("4$o!"));
+
+        let mut copy = GameOfLife::new();
+        load(&rle, &mut copy, (0, 0)).unwrap();
+        assert_eq!(copy.get_alive_cells(), gol.get_alive_cells());
+    }
+}
@@ -1,40 +1,143 @@
-use rand::Rng;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::thread;
+
+use super::history::{Change, Diff, Entry, History, Snapshot};
+use super::rule::Rule;
+use super::rule_table::{MAX_INPUTS, TableCache};
+use super::soup::Soup;
+use super::stability::{PeriodDetector, Stability};
+use super::topology::Topology;
+use super::transform::bounding_box;
+
+/// number of alive cells from which the steps are computed in parallel
+const PARALLEL_THRESHOLD: usize = 4096;
+
+/// side of the square tiles, as a power of two, used to split the cells between threads
+const TILE_SHIFT: u32 = 6;
+
+/// squares drawn by data_as_str for the states from 2
+const STATE_SQUARES: [char; 7] = ['🟥', '🟧', '🟨', '🟩', '🟦', '🟪', '🟫'];
+
+/// the alive cells and the cells in other states of a generation
+type Generation = (HashSet<(i32, i32)>, HashMap<(i32, i32), u8>);
 
 /// alive_cells contains the position of the alive cells
+/// cell_states contains the cells in the states from 2, the decay states of Generations rules
+/// or any state of the rule tables, so every cell that isn't dead is in one of both
 /// step count the steps made in the simulation
+/// rule decides which cells are born and which survive on each step
+/// topology decides the shape of the universe and how its edges are joined
+/// history keeps the previous states to undo steps and edits
+/// detector finds repeated generations, edited is set when the cells change outside of a step
+/// threads is the number of threads used to compute the steps of big universes
+/// offsets are the positions of the neighbours of a cell in the neighbourhood of the rule
+/// rule_table is the table of the rule, if it is given by one, with the next states found so far
 pub struct GameOfLife {
     alive_cells: HashSet<(i32, i32)>,
-    step: u32,
+    cell_states: HashMap<(i32, i32), u8>,
+    step: u64,
+    rule: Rule,
+    topology: Topology,
+    history: History,
+    detector: PeriodDetector,
+    edited: bool,
+    threads: usize,
+    offsets: Vec<(i32, i32)>,
+    rule_table: Option<TableCache>,
 }
 
 impl GameOfLife {
     pub fn new() -> Self {
+        GameOfLife::with_rule(Rule::conway())
+    }
+
+    pub fn with_rule(rule: Rule) -> Self {
+        let offsets = rule.get_neighbour_offsets();
+        let rule_table = rule.get_rule_table().map(TableCache::new);
         GameOfLife {
             alive_cells: HashSet::new(),
+            cell_states: HashMap::new(),
             step: 0,
+            rule,
+            topology: Topology::Infinite,
+            history: History::new(0),
+            detector: PeriodDetector::new(),
+            edited: true,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            offsets,
+            rule_table,
         }
     }
 
+    /// change the rule used for the next steps
+    /// the alive cells and the step counter are kept
+    /// cells in states the new rule doesn't have are removed
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.offsets = rule.get_neighbour_offsets();
+        self.rule_table = rule.get_rule_table().map(TableCache::new);
+        self.cell_states
+            .retain(|_, &mut state| state < rule.get_states());
+        self.rule = rule;
+        self.edited = true;
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule.clone()
+    }
+
+    /// change the shape of the universe
+    /// the alive cells outside of the new board are removed
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+        self.alive_cells.retain(|&(x, y)| topology.contains(x, y));
+        self.cell_states
+            .retain(|&(x, y), _| topology.contains(x, y));
+        self.edited = true;
+    }
+
+    pub fn get_topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// set the number of threads used to compute a step
+    /// at least one thread is used
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
     /// randomize an area going from (min_x, min_y) to (max_x, max_y)
     /// min_x < max_x
     /// min_y < max_y
-    pub fn randomize_area(&mut self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) {
-        let mut rng = rand::rng();
+    /// the part of the area outside of the board is ignored
+    /// returns the seed of the soup so it can be recreated
+    pub fn randomize_area(&mut self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> u64 {
+        let soup = Soup::random((min_x, min_y, max_x, max_y));
+        self.add_soup(&soup);
+        soup.seed
+    }
 
-        for i in min_x..=max_x {
-            for j in min_y..=max_y {
-                if rng.random::<f64>() < 0.2 {
-                    self.alive_cells.insert((i, j));
-                }
-            }
+    /// make the cells of a soup alive
+    /// the part of the soup outside of the board is ignored
+    pub fn add_soup(&mut self, soup: &Soup) {
+        for (x, y) in soup.get_cells() {
+            self.set_cell_status(x, y, true);
         }
     }
 
     /// change the cell status from dead to alive or viceversa
     /// in a given position
+    /// positions outside of the board are ignored
     pub fn change_cell_status(&mut self, pos_x: i32, pos_y: i32) {
+        if !self.topology.contains(pos_x, pos_y) {
+            return;
+        }
+        self.edited = true;
+        self.cell_states.remove(&(pos_x, pos_y));
         match self.alive_cells.contains(&(pos_x, pos_y)) {
             true => {
                 self.alive_cells.remove(&(pos_x, pos_y));
@@ -45,72 +148,617 @@ impl GameOfLife {
         }
     }
 
+    /// make the cell in a given position alive or dead
+    /// positions outside of the board are ignored
+    pub fn set_cell_status(&mut self, pos_x: i32, pos_y: i32, alive: bool) {
+        if !self.topology.contains(pos_x, pos_y) {
+            return;
+        }
+        self.edited = true;
+        self.cell_states.remove(&(pos_x, pos_y));
+        match alive {
+            true => self.alive_cells.insert((pos_x, pos_y)),
+            false => self.alive_cells.remove(&(pos_x, pos_y)),
+        };
+    }
+
+    /// returns true if the cell in a given position is alive
+    pub fn get_cell_status(&self, pos_x: i32, pos_y: i32) -> bool {
+        self.alive_cells.contains(&(pos_x, pos_y))
+    }
+
+    /// get the state of the cell in a given position
+    /// 0 is dead, 1 alive and the next states are the decay states of Generations rules
+    /// or the states of a rule table
+    pub fn get_cell_state(&self, pos_x: i32, pos_y: i32) -> u8 {
+        match self.alive_cells.contains(&(pos_x, pos_y)) {
+            true => 1,
+            false => self.cell_states.get(&(pos_x, pos_y)).copied().unwrap_or(0),
+        }
+    }
+
+    /// change the state of the cell in a given position
+    /// states the rule doesn't have and positions outside of the board are ignored
+    pub fn set_cell_state(&mut self, pos_x: i32, pos_y: i32, state: u8) {
+        if state >= self.rule.get_states() {
+            return;
+        }
+        self.set_cell_status(pos_x, pos_y, state == 1);
+        if state > 1 && self.topology.contains(pos_x, pos_y) {
+            self.cell_states.insert((pos_x, pos_y), state);
+        }
+    }
+
+    pub fn get_cell_states(&self) -> &HashMap<(i32, i32), u8> {
+        &self.cell_states
+    }
+
+    /// change the number of previous states kept to undo
+    /// the history is disabled by default, a limit of 0 disables it
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.set_limit(limit);
+    }
+
+    pub fn get_history_limit(&self) -> usize {
+        self.history.get_limit()
+    }
+
+    /// save the current state before the user edits the cells
+    /// so the whole edit can be undone at once
+    pub fn record_edit(&mut self) {
+        // avoid copying the cells when the history is disabled
+        if self.history.get_limit() > 0 {
+            let snapshot = self.snapshot();
+            self.history.record(Entry::Edit(snapshot));
+        }
+    }
+
+    /// restore the state before the last step or edit
+    /// returns false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.history.undo() {
+            Some(entry) => {
+                let entry = self.restore(entry, true);
+                self.history.keep_redo(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// apply again the last undone step or edit
+    /// returns false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        match self.history.redo() {
+            Some(entry) => {
+                let entry = self.restore(entry, false);
+                self.history.keep_undo(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// go back to the previous generation
+    /// the edits made after it are undone too
+    /// returns false if there is no previous generation in the history
+    pub fn step_back(&mut self) -> bool {
+        if !self.history.contains(Change::Generation) {
+            return false;
+        }
+        loop {
+            match self.history.last_change() {
+                Some(Change::Generation) => return self.undo(),
+                Some(Change::Edit) => {
+                    self.undo();
+                }
+                None => return false,
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            alive_cells: self.alive_cells.clone(),
+            cell_states: self.cell_states.clone(),
+            step: self.step,
+        }
+    }
+
+    /// go to the state before a change, or after it when it is redone
+    /// returns the entry that goes back to the current state
+    fn restore(&mut self, entry: Entry, undo: bool) -> Entry {
+        self.edited = true;
+        match entry {
+            Entry::Generation(diff) => {
+                for &(cell, before, after) in &diff.cells {
+                    let state = match undo {
+                        true => before,
+                        false => after,
+                    };
+                    self.alive_cells.remove(&cell);
+                    self.cell_states.remove(&cell);
+                    match state {
+                        0 => {}
+                        1 => {
+                            self.alive_cells.insert(cell);
+                        }
+                        state => {
+                            self.cell_states.insert(cell, state);
+                        }
+                    }
+                }
+                self.step = match undo {
+                    true => diff.step,
+                    false => diff.step + 1,
+                };
+                Entry::Generation(diff)
+            }
+            Entry::Edit(snapshot) => {
+                let current = self.snapshot();
+                self.alive_cells = snapshot.alive_cells;
+                self.cell_states = snapshot.cell_states;
+                self.step = snapshot.step;
+                Entry::Edit(current)
+            }
+        }
+    }
+
+    /// save the cells whose state changes in the next generation
+    /// only when the history is enabled
+    fn record_generation(
+        &mut self,
+        alive_cells: &HashSet<(i32, i32)>,
+        cell_states: &HashMap<(i32, i32), u8>,
+    ) {
+        if self.history.get_limit() == 0 {
+            return;
+        }
+        // the state of a cell in the next generation
+        let next = |cell| match alive_cells.contains(&cell) {
+            true => 1,
+            false => cell_states.get(&cell).copied().unwrap_or(0),
+        };
+        let mut cells = Vec::new();
+        for &cell in self.alive_cells.iter().chain(self.cell_states.keys()) {
+            let (before, after) = (self.get_cell_state(cell.0, cell.1), next(cell));
+            if before != after {
+                cells.push((cell, before, after));
+            }
+        }
+        for &cell in alive_cells.iter().chain(cell_states.keys()) {
+            if self.get_cell_state(cell.0, cell.1) == 0 {
+                cells.push((cell, 0, next(cell)));
+            }
+        }
+        self.history.record(Entry::Generation(Diff {
+            cells,
+            step: self.step,
+        }));
+    }
+
     /// do a step in the simulation
     /// it calculates the next position of the cells
-    /// following the 4 main rules 
+    /// following the survival and birth conditions of the rule
+    /// big universes are split in tiles computed in different threads
     pub fn step(&mut self) {
+        // the generations before an edit can't repeat
+        if self.edited {
+            self.detector.clear();
+            self.detector
+                .observe(&self.alive_cells, &self.cell_states, self.step);
+            self.edited = false;
+        }
+
+        let (alive_cells, cell_states) = match self.rule_table.take() {
+            Some(mut table) => {
+                let next = self.next_states(&mut table);
+                self.rule_table = Some(table);
+                next
+            }
+            None => {
+                let larger_than_life =
+                    self.rule.get_range() > 1 && self.topology == Topology::Infinite;
+                let parallel = self.threads > 1 && self.alive_cells.len() >= PARALLEL_THRESHOLD;
+                let alive_cells = match (larger_than_life, parallel) {
+                    (true, _) => self.next_cells_by_sums(),
+                    (false, true) => self.next_cells_parallel(),
+                    (false, false) => self.next_cells(self.alive_cells.iter()),
+                };
+                let cell_states = match self.rule.is_generations() {
+                    true => self.next_decaying_cells(&alive_cells),
+                    false => HashMap::new(),
+                };
+                (alive_cells, cell_states)
+            }
+        };
+        self.record_generation(&alive_cells, &cell_states);
+        self.alive_cells = alive_cells;
+        self.cell_states = cell_states;
         self.step += 1;
+        self.detector
+            .observe(&self.alive_cells, &self.cell_states, self.step);
+    }
+
+    /// the alive cells that don't survive start decaying
+    /// and the decaying cells go to the next state or die after the last one
+    fn next_decaying_cells(
+        &self,
+        new_alive_cells: &HashSet<(i32, i32)>,
+    ) -> HashMap<(i32, i32), u8> {
+        let states = self.rule.get_states();
+        let mut decaying_cells: HashMap<(i32, i32), u8> = self
+            .cell_states
+            .iter()
+            .filter(|&(_, &state)| state + 1 < states)
+            .map(|(&cell, &state)| (cell, state + 1))
+            .collect();
+        for &cell in self.alive_cells.difference(new_alive_cells) {
+            decaying_cells.insert(cell, 2);
+        }
+        decaying_cells
+    }
+
+    /// compute the next state of the cells with a rule table
+    /// only the cells that aren't dead and their neighbours are looked up,
+    /// the table keeps the next state of each neighbourhood for the next steps
+    fn next_states(&self, table: &mut TableCache) -> Generation {
+        let mut cells = HashSet::new();
+        for &(x, y) in self.alive_cells.iter().chain(self.cell_states.keys()) {
+            cells.insert((x, y));
+            cells.extend(self.get_neighbors(x, y));
+        }
+
+        let mut alive_cells = HashSet::new();
+        let mut cell_states = HashMap::new();
+        for (x, y) in cells {
+            // the cell and its neighbours in the order of the table, dead outside of the board
+            let mut states = [0; MAX_INPUTS];
+            let neighbourhood = std::iter::once((0, 0)).chain(self.offsets.iter().cloned());
+            for (state, (dx, dy)) in states.iter_mut().zip(neighbourhood) {
+                let cell = match self.topology {
+                    Topology::Infinite => Some((x + dx, y + dy)),
+                    topology => topology.wrap(x + dx, y + dy),
+                };
+                *state = cell.map_or(0, |(x, y)| self.get_cell_state(x, y));
+            }
+            let state = table.get_next_state(&states[..self.offsets.len() + 1]);
+            match state {
+                0 => {}
+                1 => {
+                    alive_cells.insert((x, y));
+                }
+                state => {
+                    cell_states.insert((x, y), state);
+                }
+            }
+        }
+        (alive_cells, cell_states)
+    }
+
+    /// compute the next generation splitting the tiles around the alive cells between the threads
+    /// each tile is computed on a grid of its cells and the cells around it, so the neighbours
+    /// are counted without looking up the set, and the cells of every thread are merged once
+    fn next_cells_parallel(&self) -> HashSet<(i32, i32)> {
+        let range = self.rule.get_range() as i32;
+        let size = 1 << TILE_SHIFT;
+        let tile = |(x, y): (i32, i32)| (x >> TILE_SHIFT, y >> TILE_SHIFT);
+        let mut tiles: HashMap<(i32, i32), Vec<(i32, i32)>> = HashMap::new();
+        for &cell in &self.alive_cells {
+            tiles.entry(tile(cell)).or_default().push(cell);
+        }
+
+        // the tiles where a cell can be alive in the next generation
+        // cells near the edges of a tile or of the board have neighbours in other tiles
+        let bounds = self.topology.get_bounds();
+        let near_edge = |(x, y): (i32, i32)| {
+            let (tx, ty) = (x & (size - 1), y & (size - 1));
+            let near_tile = tx < range || ty < range || tx >= size - range || ty >= size - range;
+            near_tile
+                || bounds.is_some_and(|(min_x, min_y, max_x, max_y)| {
+                    x - min_x < range || y - min_y < range || max_x - x < range || max_y - y < range
+                })
+        };
+        let mut targets: HashSet<(i32, i32)> = tiles.keys().cloned().collect();
+        for &cell in self.alive_cells.iter().filter(|&&cell| near_edge(cell)) {
+            targets.extend(self.get_neighbors(cell.0, cell.1).into_iter().map(tile));
+        }
+        let targets: Vec<(i32, i32)> = targets.into_iter().collect();
+        let chunk_size = targets.len().div_ceil(self.threads);
+
+        let results: Vec<Vec<(i32, i32)>> = thread::scope(|scope| {
+            let handles: Vec<_> = targets
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let tiles = &tiles;
+                    scope.spawn(move || {
+                        let mut cells = Vec::new();
+                        for &target in chunk {
+                            self.next_tile(target, tiles.get(&target), &mut cells);
+                        }
+                        cells
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("step thread panicked"))
+                .collect()
+        });
+
+        let mut new_alive_cells = HashSet::with_capacity(results.iter().map(Vec::len).sum());
+        for cells in results {
+            new_alive_cells.extend(cells);
+        }
+        new_alive_cells
+    }
 
+    /// add the cells of a tile that are alive in the next generation
+    /// cells are the alive cells of the tile, the cells around it are looked up in the set
+    fn next_tile(
+        &self,
+        (tx, ty): (i32, i32),
+        cells: Option<&Vec<(i32, i32)>>,
+        next: &mut Vec<(i32, i32)>,
+    ) {
+        let range = self.rule.get_range() as i32;
+        let size = 1 << TILE_SHIFT;
+        let side = size + 2 * range;
+        let (left, bottom) = ((tx << TILE_SHIFT) - range, (ty << TILE_SHIFT) - range);
+        let index = |x: i32, y: i32| ((y - bottom) * side + x - left) as usize;
+
+        // grid of the tile and a border of the size of the range around it
+        let mut grid = vec![false; (side * side) as usize];
+        for &(x, y) in cells.into_iter().flatten() {
+            grid[index(x, y)] = true;
+        }
+        for y in bottom..bottom + side {
+            for x in left..left + side {
+                let inside = x >= left + range
+                    && x < left + range + size
+                    && y >= bottom + range
+                    && y < bottom + range + size;
+                // cells of the tile outside a finite board are read from where they wrap
+                if inside && self.topology.contains(x, y) {
+                    continue;
+                }
+                let cell = match self.topology {
+                    Topology::Infinite => Some((x, y)),
+                    topology => topology.wrap(x, y),
+                };
+                grid[index(x, y)] = cell.is_some_and(|cell| self.alive_cells.contains(&cell));
+            }
+        }
+
+        let offsets: Vec<isize> = self
+            .offsets
+            .iter()
+            .map(|&(dx, dy)| (dy * side + dx) as isize)
+            .collect();
+        for y in bottom + range..bottom + range + size {
+            for x in left + range..left + range + size {
+                if !self.topology.contains(x, y) {
+                    continue;
+                }
+                let i = index(x, y);
+                let alive = grid[i];
+                let alive_next = match self.rule.is_totalistic() {
+                    true => {
+                        let neighbours = offsets
+                            .iter()
+                            .filter(|&&offset| grid[(i as isize + offset) as usize])
+                            .count() as u32;
+                        match alive {
+                            true => self.rule.survives(neighbours),
+                            false => self.rule.is_born(neighbours),
+                        }
+                    }
+                    false => {
+                        // the block starts from the top left, y grows upwards
+                        let block = (0..9)
+                            .filter(|bit| grid[index(x + bit % 3 - 1, y + 1 - bit / 3)])
+                            .fold(0, |block, bit| block | 1 << bit);
+                        self.rule.is_alive_next(block as usize)
+                    }
+                };
+                // decaying cells can't be born
+                if alive_next && (alive || !self.cell_states.contains_key(&(x, y))) {
+                    next.push((x, y));
+                }
+            }
+        }
+    }
+
+    /// compute the cells that will be alive in the next generation
+    /// among the given alive cells and their dead neighbours
+    fn next_cells<'a>(&self, cells: impl Iterator<Item = &'a (i32, i32)>) -> HashSet<(i32, i32)> {
         let mut new_alive_cells = HashSet::new();
 
-        for cell in self.alive_cells.iter() {
-            // Check for rules 1 & 2 & 3
-            let neighbours_count = self.count_alive_neighbours(cell.0, cell.1);
-            if neighbours_count == 2 || neighbours_count == 3 {
+        for cell in cells {
+            // check if the alive cell survives
+            if self.is_alive_next(cell.0, cell.1, true) {
                 new_alive_cells.insert(*cell);
             }
 
-            // check for rule 4
+            // check if a dead neighbour is born, decaying cells can't be born
             for neighbour in self.get_neighbors(cell.0, cell.1) {
-                let nc = self.count_alive_neighbours(neighbour.0, neighbour.1);
-                if nc == 3 {
+                if self.alive_cells.contains(&neighbour)
+                    || self.cell_states.contains_key(&neighbour)
+                {
+                    continue;
+                }
+                if self.is_alive_next(neighbour.0, neighbour.1, false) {
                     new_alive_cells.insert(neighbour);
                 }
             }
         }
 
-        self.alive_cells = new_alive_cells;
+        new_alive_cells
     }
 
-    /// count the number of alive neighbours around a given cell position
-    pub fn count_alive_neighbours(&self, pos_x: i32, pos_y: i32) -> u32 {
-        let mut count = 0;
-        for i in [-1, 0, 1] {
-            for j in [-1, 0, 1] {
-                if i == 0 && j == 0 {
-                    continue;
+    /// returns true if a cell is alive in the next generation
+    /// non-totalistic rules look up the 3x3 block around the cell, the others count its neighbours
+    fn is_alive_next(&self, x: i32, y: i32, alive: bool) -> bool {
+        if self.rule.is_totalistic() {
+            let neighbours = self.count_alive_neighbours(x, y);
+            return match alive {
+                true => self.rule.survives(neighbours),
+                false => self.rule.is_born(neighbours),
+            };
+        }
+        let mut block = 0;
+        for bit in 0..9 {
+            // the block starts from the top left, y grows upwards
+            let (x, y) = (x + bit % 3 - 1, y + 1 - bit / 3);
+            let cell = match self.topology {
+                Topology::Infinite => Some((x, y)),
+                topology => topology.wrap(x, y),
+            };
+            if cell.is_some_and(|cell| self.alive_cells.contains(&cell)) {
+                block |= 1 << bit;
+            }
+        }
+        self.rule.is_alive_next(block)
+    }
+
+    /// compute the next generation of Larger than Life rules in the infinite plane
+    /// the tiles around the alive cells are computed one at a time on a grid with the tile
+    /// and the cells in range around it, the alive cells of each row of the grid are added
+    /// in running sums, so the neighbours of a cell are counted with a subtraction per row
+    fn next_cells_by_sums(&self) -> HashSet<(i32, i32)> {
+        let range = self.rule.get_range() as i64;
+        let neighbourhood = self.rule.get_neighbourhood();
+        let rows: Vec<(i64, (i64, i64))> = (-range..=range)
+            .map(|dy| {
+                let (first, last) = neighbourhood.get_row(range as i32, dy as i32);
+                (dy, (first as i64, last as i64))
+            })
+            .collect();
+        let tile = |(x, y): (i32, i32)| (x >> TILE_SHIFT, y >> TILE_SHIFT);
+        let mut tiles: HashMap<(i32, i32), Vec<(i32, i32)>> = HashMap::new();
+        for &cell in &self.alive_cells {
+            tiles.entry(tile(cell)).or_default().push(cell);
+        }
+        // the range is smaller than a tile, so the cells in range of a tile are in the tiles around it
+        let around = |(tx, ty): (i32, i32)| {
+            (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (tx + dx, ty + dy)))
+        };
+        let targets: HashSet<(i32, i32)> = tiles.keys().cloned().flat_map(around).collect();
+
+        // sums[row * stride + i] is the number of alive cells in the first i columns of the row
+        // the grid has the same small size for every tile, whatever the distance between the cells
+        let size = 1i64 << TILE_SHIFT;
+        let side = size + 2 * range;
+        let stride = usize::try_from(side + 1).expect("tile grid too wide");
+        let height = usize::try_from(side).expect("tile grid too high");
+        let mut sums = vec![0u32; stride.checked_mul(height).expect("tile grid too big")];
+        let mut new_alive_cells = HashSet::new();
+        for target in targets {
+            let left = ((target.0 as i64) << TILE_SHIFT) - range;
+            let bottom = ((target.1 as i64) << TILE_SHIFT) - range;
+            sums.fill(0);
+            for &(x, y) in around(target).filter_map(|tile| tiles.get(&tile)).flatten() {
+                let (i, j) = (x as i64 - left, y as i64 - bottom);
+                if (0..side).contains(&i) && (0..side).contains(&j) {
+                    sums[j as usize * stride + i as usize + 1] = 1;
                 }
-                if self.alive_cells.contains(&(pos_x - i, pos_y - j)) {
-                    count += 1;
+            }
+            for row in sums.chunks_mut(stride) {
+                let mut total = 0;
+                for value in row.iter_mut() {
+                    total += *value;
+                    *value = total;
                 }
             }
+            let count_row = |i: i64, j: i64, (first, last): (i64, i64)| {
+                let row = &sums[j as usize * stride..];
+                row[(i + last) as usize + 1] - row[(i + first) as usize]
+            };
+
+            for j in range..range + size {
+                for i in range..range + size {
+                    let alive = count_row(i, j, (0, 0)) == 1;
+                    let neighbours = rows
+                        .iter()
+                        .map(|&(dy, row)| count_row(i, j + dy, row))
+                        .sum::<u32>()
+                        - alive as u32;
+                    // cells past the edges of the plane can't be stored
+                    let (Ok(x), Ok(y)) = (i32::try_from(left + i), i32::try_from(bottom + j))
+                    else {
+                        continue;
+                    };
+                    let next = match alive {
+                        true => self.rule.survives(neighbours),
+                        false => {
+                            !self.cell_states.contains_key(&(x, y)) && self.rule.is_born(neighbours)
+                        }
+                    };
+                    if next {
+                        new_alive_cells.insert((x, y));
+                    }
+                }
+            }
+        }
+        new_alive_cells
+    }
+
+    /// get whether the cells have become static, periodic or a translating pattern
+    /// the generations are compared since the last edit
+    pub fn get_stability(&self) -> Stability {
+        match self.edited {
+            true => Stability::Evolving,
+            false => self.detector.get_stability(),
         }
-        count
     }
 
+    /// count the number of alive neighbours around a given cell position
+    pub fn count_alive_neighbours(&self, pos_x: i32, pos_y: i32) -> u32 {
+        self.get_neighbors(pos_x, pos_y)
+            .iter()
+            .filter(|cell| self.alive_cells.contains(cell))
+            .count() as u32
+    }
+
+    /// count the cells that aren't dead, with the cells in other states than alive
+    /// of Generations rules and rule tables
     pub fn count_alive_cells(&self) -> usize {
-        self.alive_cells.len()
+        self.alive_cells.len() + self.cell_states.len()
     }
 
-    pub fn get_steps_count(&self) -> u32 {
+    pub fn get_steps_count(&self) -> u64 {
         self.step
     }
 
+    /// change the step counter, used when the cells come from another engine
+    pub(crate) fn set_steps_count(&mut self, step: u64) {
+        self.step = step;
+    }
+
+    /// get the smallest area (min_x, min_y, max_x, max_y) containing every cell that isn't dead
+    /// returns None if every cell is dead
+    pub fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)> {
+        bounding_box(self.alive_cells.iter().chain(self.cell_states.keys()))
+    }
+
+    pub fn get_alive_cells(&self) -> &HashSet<(i32, i32)> {
+        &self.alive_cells
+    }
+
+    /// get the positions around a cell in the neighbourhood of the rule
+    /// positions across the edges of the board are moved to the cell they are joined with
     fn get_neighbors(&self, x: i32, y: i32) -> Vec<(i32, i32)> {
-        vec![
-            (x + 1, y),
-            (x + 1, y + 1),
-            (x, y + 1),
-            (x - 1, y + 1),
-            (x - 1, y),
-            (x - 1, y - 1),
-            (x, y - 1),
-            (x + 1, y - 1),
-        ]
+        let neighbors = self.offsets.iter().map(|&(dx, dy)| (x + dx, y + dy));
+        match self.topology {
+            Topology::Infinite => neighbors.collect(),
+            topology => neighbors.filter_map(|(x, y)| topology.wrap(x, y)).collect(),
+        }
     }
 
     /// Convert data into a readable output
     /// Used only for testing/debug
+    /// rows go from max_y down to min_y and columns from min_x to max_x,
+    /// the states after alive cycle through the colored squares
     pub fn data_as_str(
         &self,
         mut min_x: i32,
@@ -119,14 +767,10 @@ impl GameOfLife {
         mut max_y: i32,
     ) -> String {
         if min_x > max_x {
-            let aux = min_x;
-            min_x = max_x;
-            max_x = aux;
+            std::mem::swap(&mut min_x, &mut max_x);
         }
         if min_y > max_y {
-            let aux = min_y;
-            min_y = max_y;
-            max_y = aux;
+            std::mem::swap(&mut min_y, &mut max_y);
         }
         let mut data_as_str: String = String::new();
 
@@ -137,15 +781,13 @@ impl GameOfLife {
         let mut output: Vec<Vec<char>> = vec![vec!('⬛'; width); height];
 
         // Filter only the cell in the region to draw
-        for (x, y) in self
-            .alive_cells
-            .iter()
-            .filter(|(a, b)| *a >= min_x && *a <= max_x && *b >= min_y && *b <= max_y)
-        {
-            // rotate for better diplay
-            let pos_x: usize = (max_x - x).try_into().unwrap();
-            let pos_y: usize = (y - min_y).try_into().unwrap();
-            output[pos_x][pos_y] = '⬜';
+        for ((x, y), state) in self.data_as_states((min_x, min_y, max_x, max_y)) {
+            let row: usize = (max_y - y).try_into().unwrap();
+            let col: usize = (x - min_x).try_into().unwrap();
+            output[row][col] = match state {
+                1 => '⬜',
+                state => STATE_SQUARES[(state as usize - 2) % STATE_SQUARES.len()],
+            };
         }
 
         for line in output {
@@ -159,17 +801,12 @@ impl GameOfLife {
     pub fn data_as_vec(&self, area: (i32, i32, i32, i32)) -> HashSet<(i32, i32)> {
         let (mut min_x, mut min_y, mut max_x, mut max_y) = area;
         if min_x > max_x {
-            let aux = min_x;
-            min_x = max_x;
-            max_x = aux;
+            std::mem::swap(&mut min_x, &mut max_x);
         }
         if min_y > max_y {
-            let aux = min_y;
-            min_y = max_y;
-            max_y = aux;
+            std::mem::swap(&mut min_y, &mut max_y);
         }
 
-
         // Filter only the cell in the region to draw
         self.alive_cells
             .iter()
@@ -178,10 +815,37 @@ impl GameOfLife {
             .collect()
     }
 
+    /// get the state of the cells in a certain area that aren't dead
+    pub fn data_as_states(&self, area: (i32, i32, i32, i32)) -> HashMap<(i32, i32), u8> {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = area;
+        if min_x > max_x {
+            std::mem::swap(&mut min_x, &mut max_x);
+        }
+        if min_y > max_y {
+            std::mem::swap(&mut min_y, &mut max_y);
+        }
+
+        let states = self.cell_states.iter().map(|(&cell, &state)| (cell, state));
+        self.alive_cells
+            .iter()
+            .map(|&cell| (cell, 1))
+            .chain(states)
+            .filter(|&((a, b), _)| a >= min_x && a <= max_x && b >= min_y && b <= max_y)
+            .collect()
+    }
+
     /// delete all alive cells and restart the step counter
     pub fn clear_cells(&mut self) {
         self.alive_cells.clear();
+        self.cell_states.clear();
         self.step = 0;
+        self.edited = true;
+    }
+}
+
+impl Default for GameOfLife {
+    fn default() -> Self {
+        GameOfLife::new()
     }
 }
 
@@ -230,7 +894,7 @@ mod test {
         assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬛⬛\n⬛⬛⬛\n⬛⬛⬛\n");
         gol.change_cell_status(0, 0);
         gol.change_cell_status(0, 1);
-        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬛⬛\n⬛⬜⬜\n⬛⬛⬛\n");
+        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬜⬛\n⬛⬜⬛\n⬛⬛⬛\n");
         gol.step();
         assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬛⬛\n⬛⬛⬛\n⬛⬛⬛\n");
     }
@@ -243,11 +907,11 @@ mod test {
         gol.change_cell_status(0, 1);
         gol.change_cell_status(0, -1);
 
-        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬛⬛\n⬜⬜⬜\n⬛⬛⬛\n");
+        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬜⬛\n⬛⬜⬛\n⬛⬜⬛\n");
 
         gol.step();
         // the cell in the center is still alive
-        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬜⬛\n⬛⬜⬛\n⬛⬜⬛\n");
+        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬛⬛\n⬜⬜⬜\n⬛⬛⬛\n");
     }
 
     #[test]
@@ -278,4 +942,406 @@ mod test {
         // a new cell lives
         assert_eq!(gol.data_as_str(1, 2, 1, 2), "⬜⬜\n⬜⬜\n");
     }
+
+    #[test]
+    fn test_custom_rule() {
+        // HighLife: a cell with 6 neighbours is born
+        let mut gol = GameOfLife::with_rule(Rule::parse("B36/S23").unwrap());
+        gol.change_cell_status(-1, 1);
+        gol.change_cell_status(0, 1);
+        gol.change_cell_status(1, 1);
+        gol.change_cell_status(-1, -1);
+        gol.change_cell_status(0, -1);
+        gol.change_cell_status(1, -1);
+        gol.step();
+        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬜⬛\n⬛⬜⬛\n⬛⬜⬛\n");
+
+        // the same pattern under Conway's rule leaves the center empty
+        gol.clear_cells();
+        gol.set_rule(Rule::conway());
+        gol.change_cell_status(-1, 1);
+        gol.change_cell_status(0, 1);
+        gol.change_cell_status(1, 1);
+        gol.change_cell_status(-1, -1);
+        gol.change_cell_status(0, -1);
+        gol.change_cell_status(1, -1);
+        gol.step();
+        assert_eq!(gol.data_as_str(-1, 1, -1, 1), "⬛⬜⬛\n⬛⬛⬛\n⬛⬜⬛\n");
+    }
+
+    #[test]
+    fn test_torus() {
+        let mut gol = GameOfLife::new();
+        gol.set_topology(Topology::parse("T6,6").unwrap());
+        // glider moving to the south east
+        for (x, y) in [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)] {
+            gol.change_cell_status(x, y);
+        }
+        let start = gol.get_alive_cells().clone();
+        // after 4 steps per cell of the board it is back to the start
+        for _ in 0..24 {
+            gol.step();
+            assert_eq!(gol.count_alive_cells(), 5);
+        }
+        assert_eq!(gol.get_alive_cells(), &start);
+    }
+
+    #[test]
+    fn test_plane() {
+        let mut gol = GameOfLife::new();
+        gol.set_topology(Topology::parse("P4,4").unwrap());
+        // cells outside of the board can't be added
+        gol.change_cell_status(2, 0);
+        assert_eq!(gol.count_alive_cells(), 0);
+
+        // a blinker on the edge can't oscillate
+        gol.change_cell_status(-1, 1);
+        gol.change_cell_status(0, 1);
+        gol.change_cell_status(1, 1);
+        gol.step();
+        assert_eq!(gol.count_alive_cells(), 2);
+        assert!(gol.get_cell_status(0, 1));
+        assert!(gol.get_cell_status(0, 0));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut gol = GameOfLife::new();
+        gol.set_history_limit(10);
+
+        // horizontal blinker
+        gol.record_edit();
+        gol.change_cell_status(-1, 0);
+        gol.change_cell_status(0, 0);
+        gol.change_cell_status(1, 0);
+        let horizontal = gol.get_alive_cells().clone();
+        gol.step();
+        let vertical = gol.get_alive_cells().clone();
+
+        // a stray click is undone
+        gol.record_edit();
+        gol.change_cell_status(5, 5);
+        assert!(gol.undo());
+        assert_eq!(gol.get_alive_cells(), &vertical);
+        assert!(gol.redo());
+        assert!(gol.get_cell_status(5, 5));
+
+        // going back a generation undoes the edit too
+        assert!(gol.step_back());
+        assert_eq!(gol.get_alive_cells(), &horizontal);
+        assert_eq!(gol.get_steps_count(), 0);
+        assert!(!gol.step_back());
+
+        // undo the first edit
+        assert!(gol.undo());
+        assert_eq!(gol.count_alive_cells(), 0);
+        assert!(!gol.undo());
+
+        // redo everything
+        assert!(gol.redo());
+        assert!(gol.redo());
+        assert_eq!(gol.get_steps_count(), 1);
+        assert!(gol.redo());
+        assert!(gol.get_cell_status(5, 5));
+        assert!(!gol.redo());
+    }
+
+    #[test]
+    fn test_undo_generations() {
+        // Brian's Brain changes the decay states on every step
+        let mut gol = GameOfLife::with_rule(Rule::parse("/2/3").unwrap());
+        gol.set_history_limit(10);
+        gol.add_soup(&Soup::new(4, (-5, -5, 5, 5)));
+        let mut generations = vec![gol.snapshot()];
+        for _ in 0..5 {
+            gol.step();
+            generations.push(gol.snapshot());
+        }
+        assert!(!gol.get_cell_states().is_empty());
+
+        for generation in generations.iter().rev().skip(1) {
+            assert!(gol.step_back());
+            assert_eq!(&gol.snapshot(), generation);
+        }
+        assert!(!gol.step_back());
+        for generation in generations.iter().skip(1) {
+            assert!(gol.redo());
+            assert_eq!(&gol.snapshot(), generation);
+        }
+    }
+
+    #[test]
+    fn test_stability() {
+        let mut gol = GameOfLife::new();
+        // block
+        for (x, y) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            gol.change_cell_status(x, y);
+        }
+        gol.step();
+        assert_eq!(gol.get_stability(), Stability::Static);
+
+        // blinker
+        gol.clear_cells();
+        for (x, y) in [(-1, 0), (0, 0), (1, 0)] {
+            gol.change_cell_status(x, y);
+        }
+        gol.step();
+        assert_eq!(gol.get_stability(), Stability::Evolving);
+        gol.step();
+        assert_eq!(gol.get_stability(), Stability::Periodic { period: 2 });
+
+        // glider
+        gol.clear_cells();
+        for (x, y) in [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)] {
+            gol.change_cell_status(x, y);
+        }
+        for _ in 0..4 {
+            gol.step();
+        }
+        assert_eq!(
+            gol.get_stability(),
+            Stability::Translating {
+                period: 4,
+                dx: 1,
+                dy: -1
+            }
+        );
+
+        // an edit starts the detection again
+        gol.change_cell_status(10, 10);
+        assert_eq!(gol.get_stability(), Stability::Evolving);
+        gol.step();
+        assert_eq!(gol.get_stability(), Stability::Evolving);
+    }
+
+    #[test]
+    fn test_seeds_rule() {
+        // Seeds: every alive cell dies, two neighbours give birth
+        let mut gol = GameOfLife::with_rule(Rule::parse("B2/S").unwrap());
+        gol.change_cell_status(0, 0);
+        gol.change_cell_status(0, 1);
+        gol.step();
+        assert_eq!(gol.count_alive_cells(), 4);
+        assert_eq!(
+            gol.data_as_str(-1, 2, -1, 2),
+            "⬛⬛⬛⬛\n⬜⬛⬜⬛\n⬜⬛⬜⬛\n⬛⬛⬛⬛\n"
+        );
+    }
+
+    #[test]
+    fn test_generations_rule() {
+        // Brian's Brain: alive cells never survive and decay for one generation
+        let mut gol = GameOfLife::with_rule(Rule::parse("/2/3").unwrap());
+        gol.change_cell_status(0, 0);
+        gol.change_cell_status(1, 0);
+        gol.step();
+        assert_eq!(gol.get_cell_state(0, 0), 2);
+        assert_eq!(gol.get_cell_state(1, 0), 2);
+        assert_eq!(gol.get_alive_cells().len(), 4);
+        // the decaying cells aren't dead
+        assert_eq!(gol.count_alive_cells(), 6);
+        assert_eq!(gol.get_cell_state(0, 1), 1);
+        gol.step();
+        assert_eq!(gol.get_cell_state(0, 0), 0);
+        assert_eq!(gol.get_cell_state(0, 1), 2);
+        assert_eq!(gol.get_cell_states().len(), 4);
+        assert_eq!(gol.get_alive_cells().len(), 6);
+        assert_eq!(gol.count_alive_cells(), 10);
+        assert_eq!(gol.data_as_states((0, 0, 0, 2)).get(&(0, 2)), Some(&1));
+
+        // a decaying cell with two alive neighbours isn't born
+        gol.clear_cells();
+        gol.set_cell_state(0, 0, 2);
+        gol.set_cell_state(-1, 1, 1);
+        gol.set_cell_state(1, 1, 1);
+        gol.step();
+        assert_eq!(gol.get_cell_state(0, 0), 0);
+        assert_eq!(gol.get_cell_state(0, 1), 1);
+
+        // states are lost in rules without them
+        gol.set_cell_state(5, 5, 2);
+        gol.set_rule(Rule::conway());
+        assert_eq!(gol.get_cell_state(5, 5), 0);
+        gol.set_cell_state(5, 5, 2);
+        assert_eq!(gol.get_cell_state(5, 5), 0);
+    }
+
+    #[test]
+    fn test_non_totalistic_rule() {
+        // only two neighbours on adjacent edges give a birth
+        let mut gol = GameOfLife::with_rule(Rule::parse("B2e/S").unwrap());
+        gol.set_cell_status(0, 1, true);
+        gol.set_cell_status(1, 0, true);
+        gol.step();
+        let expected: HashSet<(i32, i32)> = [(0, 0), (1, 1)].into_iter().collect();
+        assert_eq!(gol.get_alive_cells(), &expected);
+
+        // a cell is born below an alive cell, so the cells fall
+        let down = format!("MAP{}I{}", "A".repeat(21), "A".repeat(64));
+        let mut gol = GameOfLife::with_rule(Rule::parse(&down).unwrap());
+        gol.set_cell_status(3, 5, true);
+        gol.step();
+        gol.step();
+        let expected: HashSet<(i32, i32)> = [(3, 3)].into_iter().collect();
+        assert_eq!(gol.get_alive_cells(), &expected);
+
+        // and wrap around the torus
+        gol.set_topology(Topology::parse("T10,10").unwrap());
+        for _ in 0..10 {
+            gol.step();
+        }
+        assert_eq!(gol.get_alive_cells(), &expected);
+    }
+
+    #[test]
+    fn test_rule_table() {
+        // an electron moving along a Wireworld wire
+        let mut gol = GameOfLife::with_rule(Rule::parse("Wireworld").unwrap());
+        for x in 0..8 {
+            gol.set_cell_state(x, 0, 3);
+        }
+        gol.set_cell_state(0, 0, 2);
+        gol.set_cell_state(1, 0, 1);
+        for _ in 0..3 {
+            gol.step();
+        }
+        assert_eq!(gol.get_cell_state(4, 0), 1);
+        assert_eq!(gol.get_cell_state(3, 0), 2);
+        assert_eq!(gol.get_cell_state(0, 0), 3);
+        assert_eq!(gol.get_alive_cells().len(), 1);
+        assert_eq!(gol.get_cell_states().len(), 7);
+        assert_eq!(gol.count_alive_cells(), 8);
+        assert_eq!(gol.get_bounding_box(), Some((0, 0, 7, 0)));
+
+        // the electron reaches the end and the wire stays
+        for _ in 0..5 {
+            gol.step();
+        }
+        assert!(gol.get_alive_cells().is_empty());
+        assert_eq!(gol.count_alive_cells(), 8);
+        assert!((0..8).all(|x| gol.get_cell_state(x, 0) == 3));
+
+        // a wire without electrons isn't extinct
+        let mut gol = GameOfLife::with_rule(Rule::parse("Wireworld").unwrap());
+        for x in 0..3 {
+            gol.set_cell_state(x, 0, 3);
+        }
+        gol.step();
+        gol.step();
+        assert_eq!(gol.get_stability(), Stability::Static);
+        assert_eq!(gol.count_alive_cells(), 3);
+    }
+
+    #[test]
+    fn test_neighbourhoods() {
+        // two cells of a hexagonal grid share two neighbours
+        let mut gol = GameOfLife::with_rule(Rule::parse("B2/S34H").unwrap());
+        gol.set_cell_status(0, 0, true);
+        gol.set_cell_status(1, 0, true);
+        gol.step();
+        let expected: HashSet<(i32, i32)> = [(0, 1), (1, -1)].into_iter().collect();
+        assert_eq!(gol.get_alive_cells(), &expected);
+
+        let mut gol = GameOfLife::with_rule(Rule::parse("B1/S1V").unwrap());
+        gol.set_cell_status(0, 0, true);
+        gol.step();
+        let expected: HashSet<(i32, i32)> =
+            [(1, 0), (-1, 0), (0, 1), (0, -1)].into_iter().collect();
+        assert_eq!(gol.get_alive_cells(), &expected);
+        assert_eq!(gol.count_alive_neighbours(0, 0), 4);
+    }
+
+    #[test]
+    fn test_larger_than_life() {
+        // the running sums in the infinite plane give the same cells
+        // as counting every neighbour in a board big enough to not be reached
+        for rulestring in [
+            "R5,C0,M1,S34..58,B34..45,NM",
+            "R3,C0,M0,S2..6,B4..5,NN",
+            "R2,C3,M1,S3..8,B4..6,NC",
+            "R2,C0,M0,S2..5,B3..4,NH",
+        ] {
+            let rule = Rule::parse(rulestring).unwrap();
+            let mut infinite = GameOfLife::with_rule(rule.clone());
+            let soup = Soup {
+                density: 0.5,
+                ..Soup::new(3, (-6, -6, 6, 6))
+            };
+            infinite.add_soup(&soup);
+            let mut bounded = GameOfLife::with_rule(rule);
+            bounded.set_topology(Topology::parse("P200,200").unwrap());
+            bounded.add_soup(&soup);
+            for _ in 0..3 {
+                infinite.step();
+                bounded.step();
+                assert_eq!(infinite.get_alive_cells(), bounded.get_alive_cells());
+                assert_eq!(infinite.get_cell_states(), bounded.get_cell_states());
+            }
+            assert!(infinite.count_alive_cells() > 0, "{}", rulestring);
+        }
+
+        let mut gol = GameOfLife::with_rule(Rule::parse("R2,C0,M0,S1..24,B1..24,NM").unwrap());
+        gol.change_cell_status(0, 0);
+        assert_eq!(gol.count_alive_neighbours(2, 2), 1);
+        assert_eq!(gol.count_alive_neighbours(3, 0), 0);
+        // the cell has no neighbours to survive and every cell around it is born
+        gol.step();
+        assert_eq!(gol.count_alive_cells(), 24);
+        assert!(!gol.get_cell_status(0, 0));
+
+        // cells far apart don't need the area between them
+        let mut gol = GameOfLife::with_rule(Rule::parse("R2,C0,M0,S1..24,B1..24,NM").unwrap());
+        for (x, y) in [(0, 0), (100000, 100000), (i32::MAX, i32::MIN)] {
+            gol.set_cell_status(x, y, true);
+        }
+        gol.step();
+        assert_eq!(gol.count_alive_cells(), 24 * 2 + 8);
+        assert!(gol.get_cell_status(100002, 99998));
+        assert!(gol.get_cell_status(i32::MAX - 2, i32::MIN + 2));
+    }
+
+    #[test]
+    fn test_parallel_step() {
+        for (rulestring, topology) in [
+            ("B3/S23", "T1000,1000"),
+            ("B3/S23", "T150,120"),
+            ("B2-a3/S12ck", "K150,150"),
+            ("/2/3", "T1000,1000"),
+            ("B2/S34H", "P150,150"),
+            ("R2,C0,M1,S3..8,B4..6,NM", "T150,150"),
+        ] {
+            let rule = Rule::parse(rulestring).unwrap();
+            let mut serial = GameOfLife::with_rule(rule.clone());
+            serial.set_topology(Topology::parse(topology).unwrap());
+            serial.set_threads(1);
+            serial.add_soup(&Soup {
+                density: 0.5,
+                ..Soup::new(5, (-75, -60, 74, 59))
+            });
+            assert!(serial.count_alive_cells() >= PARALLEL_THRESHOLD);
+            let mut parallel = GameOfLife::with_rule(rule);
+            parallel.set_topology(serial.get_topology());
+            parallel.set_threads(4);
+            parallel.add_soup(&Soup {
+                density: 0.5,
+                ..Soup::new(5, (-75, -60, 74, 59))
+            });
+
+            for _ in 0..5 {
+                serial.step();
+                parallel.step();
+                assert_eq!(
+                    serial.get_alive_cells(),
+                    parallel.get_alive_cells(),
+                    "{}",
+                    rulestring
+                );
+                assert_eq!(serial.get_cell_states(), parallel.get_cell_states());
+            }
+        }
+
+        let mut gol = GameOfLife::new();
+        gol.set_threads(0);
+        assert_eq!(gol.get_threads(), 1);
+    }
 }
@@ -0,0 +1,730 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use super::game_of_life::GameOfLife;
+use super::rule::Rule;
+
+pub(crate) type NodeId = u32;
+
+pub(crate) const DEAD: NodeId = 0;
+pub(crate) const ALIVE: NodeId = 1;
+
+/// smallest level used for the root, an 8x8 square
+const MIN_ROOT_LEVEL: u8 = 3;
+
+/// largest root, the coords of its cells and its size fit in an i64
+pub(crate) const MAX_LEVEL: u8 = 62;
+
+/// largest k of a step of 2^k generations, the root must be 3 levels above it
+pub const MAX_STEP_POW2: u8 = MAX_LEVEL - 3;
+
+/// number of nodes after which the unreachable ones are discarded
+const GC_THRESHOLD: usize = 1 << 22;
+
+/// a square of 2^level x 2^level cells made of four squares of the level below
+/// level 0 nodes are single cells and don't have children
+/// north is the half with the greater y coordinates
+#[derive(Clone, Copy)]
+pub(crate) struct Node {
+    pub(crate) level: u8,
+    pub(crate) nw: NodeId,
+    pub(crate) ne: NodeId,
+    pub(crate) sw: NodeId,
+    pub(crate) se: NodeId,
+    pub(crate) population: u64,
+}
+
+/// HashLife engine
+/// the universe is a quadtree of shared nodes centered on the origin
+/// the result of advancing each node is memoized, so repetitive patterns
+/// can be advanced by 2^k generations at once
+pub struct HashLife {
+    nodes: Vec<Node>,
+    index: HashMap<(NodeId, NodeId, NodeId, NodeId), NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    step: u64,
+    rule: Rule,
+}
+
+impl HashLife {
+    pub fn new() -> Self {
+        let leaf = |population| Node {
+            level: 0,
+            nw: DEAD,
+            ne: DEAD,
+            sw: DEAD,
+            se: DEAD,
+            population,
+        };
+        let mut hashlife = HashLife {
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            step: 0,
+            rule: Rule::conway(),
+        };
+        hashlife.root = hashlife.empty_node(MIN_ROOT_LEVEL);
+        hashlife
+    }
+
+    /// fails if the rule isn't Life-like
+    pub fn with_rule(rule: Rule) -> Result<Self, String> {
+        let mut hashlife = HashLife::new();
+        hashlife.set_rule(rule)?;
+        Ok(hashlife)
+    }
+
+    /// change the rule used for the next steps
+    /// the memoized results are discarded since they depend on the rule
+    /// fails without changing the rule if it isn't Life-like,
+    /// since the base case only counts the 8 neighbours of cells with 2 states
+    pub fn set_rule(&mut self, rule: Rule) -> Result<(), String> {
+        if !rule.is_life_like() {
+            return Err(format!(
+                "HashLife only simulates Life-like rules, not {}",
+                rule
+            ));
+        }
+        self.rule = rule;
+        self.results.clear();
+        Ok(())
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule.clone()
+    }
+
+    /// change the cell status from dead to alive or viceversa
+    /// in a given position
+    pub fn change_cell_status(&mut self, pos_x: i32, pos_y: i32) {
+        let alive = self.get_cell(pos_x, pos_y);
+        self.set_cell(pos_x, pos_y, !alive);
+    }
+
+    /// returns true if the cell in the given position is alive
+    pub fn get_cell(&self, pos_x: i32, pos_y: i32) -> bool {
+        let level = self.nodes[self.root as usize].level;
+        let half = 1i64 << (level - 1);
+        let (x, y) = (pos_x as i64 + half, pos_y as i64 + half);
+        if x < 0 || y < 0 || x >= 2 * half || y >= 2 * half {
+            return false;
+        }
+        self.get_cell_in(self.root, x, y)
+    }
+
+    /// make the cell in the given position alive or dead
+    /// the universe grows if the position is outside of it
+    pub fn set_cell(&mut self, pos_x: i32, pos_y: i32, alive: bool) {
+        loop {
+            let level = self.nodes[self.root as usize].level;
+            let half = 1i64 << (level - 1);
+            let (x, y) = (pos_x as i64 + half, pos_y as i64 + half);
+            if x >= 0 && y >= 0 && x < 2 * half && y < 2 * half {
+                self.root = self.set_cell_in(self.root, x, y, alive);
+                return;
+            }
+            self.expand();
+        }
+    }
+
+    /// do a step in the simulation
+    /// nothing changes once the pattern reaches the edges of the largest universe
+    pub fn step(&mut self) {
+        // the universe is left as it was when the step fails
+        let _ = self.step_pow2(0);
+    }
+
+    /// advance the simulation by 2^k generations at once
+    /// fails without advancing if k is greater than MAX_STEP_POW2,
+    /// if the step counter would overflow or if the pattern would grow
+    /// beyond the largest universe
+    pub fn step_pow2(&mut self, k: u8) -> Result<(), String> {
+        if k > MAX_STEP_POW2 {
+            return Err(format!(
+                "can't advance 2^{} generations at once, the limit is 2^{}",
+                k, MAX_STEP_POW2
+            ));
+        }
+        let step = self
+            .step
+            .checked_add(1 << k)
+            .ok_or("the step counter would overflow")?;
+        if self.nodes.len() > GC_THRESHOLD {
+            self.collect_garbage();
+        }
+
+        // the pattern must lie in the central quarter of the root
+        // so that it can't escape the result while advancing
+        while self.level() < k + 2 || !self.is_centered(self.root) {
+            self.grow()?;
+        }
+        self.grow()?;
+
+        self.root = self.advance(self.root, k);
+        self.step = step;
+        self.shrink();
+        Ok(())
+    }
+
+    /// advance the simulation by any number of generations
+    /// decomposing it into powers of two, from the smallest one
+    /// stops at the first step that fails, keeping the generations advanced until then
+    pub fn advance_generations(&mut self, mut generations: u64) -> Result<(), String> {
+        let mut k = 0;
+        while generations > 0 {
+            match k < MAX_STEP_POW2 {
+                true => {
+                    if generations & 1 == 1 {
+                        self.step_pow2(k)?;
+                    }
+                    generations >>= 1;
+                    k += 1;
+                }
+                // the bits above the limit are done in steps of the largest size
+                false => {
+                    for _ in 0..generations {
+                        self.step_pow2(k)?;
+                    }
+                    generations = 0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn count_alive_cells(&self) -> usize {
+        self.nodes[self.root as usize].population as usize
+    }
+
+    pub fn get_steps_count(&self) -> u64 {
+        self.step
+    }
+
+    /// get the smallest area (min_x, min_y, max_x, max_y) containing every alive cell
+    /// returns None if there are no alive cells
+    /// cells that moved beyond the i32 coords are clamped to the edges of the range
+    pub fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)> {
+        let half = 1i64 << (self.level() - 1);
+        self.bounds(self.root, -half, -half)
+            .map(|(min_x, min_y, max_x, max_y)| {
+                (
+                    saturate(min_x),
+                    saturate(min_y),
+                    saturate(max_x),
+                    saturate(max_y),
+                )
+            })
+    }
+
+    /// get a new hashset with the alive cell in a certain area
+    pub fn data_as_vec(&self, area: (i32, i32, i32, i32)) -> HashSet<(i32, i32)> {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = area;
+        if min_x > max_x {
+            std::mem::swap(&mut min_x, &mut max_x);
+        }
+        if min_y > max_y {
+            std::mem::swap(&mut min_y, &mut max_y);
+        }
+
+        let mut cells = HashSet::new();
+        let half = 1i64 << (self.level() - 1);
+        self.collect_cells(
+            self.root,
+            -half,
+            -half,
+            (min_x as i64, min_y as i64, max_x as i64, max_y as i64),
+            &mut cells,
+        );
+        cells
+    }
+
+    /// delete all alive cells and restart the step counter
+    pub fn clear_cells(&mut self) {
+        self.root = self.empty_node(MIN_ROOT_LEVEL);
+        self.step = 0;
+    }
+
+    /// the node that contains the whole universe
+    pub(crate) fn get_root(&self) -> NodeId {
+        self.root
+    }
+
+    /// replace the universe with a node, its center is placed on the origin
+    /// the node can't be a single cell
+    pub(crate) fn set_root(&mut self, id: NodeId) {
+        self.root = id;
+        while self.level() < MIN_ROOT_LEVEL {
+            self.expand();
+        }
+        self.shrink();
+    }
+
+    pub(crate) fn get_node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    fn level(&self) -> u8 {
+        self.nodes[self.root as usize].level
+    }
+
+    /// get the node made of the four given nodes, creating it if it doesn't exist
+    pub(crate) fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        if let Some(&id) = self.index.get(&(nw, ne, sw, se)) {
+            return id;
+        }
+        let node = Node {
+            level: self.nodes[nw as usize].level + 1,
+            nw,
+            ne,
+            sw,
+            se,
+            population: [nw, ne, sw, se]
+                .iter()
+                .map(|&child| self.nodes[child as usize].population)
+                .sum(),
+        };
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(node);
+        self.index.insert((nw, ne, sw, se), id);
+        id
+    }
+
+    pub(crate) fn empty_node(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let child = *self.empty.last().unwrap();
+            let node = self.join(child, child, child, child);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    /// double the size of the root keeping it centered on the origin
+    fn expand(&mut self) {
+        let root = self.nodes[self.root as usize];
+        let border = self.empty_node(root.level - 1);
+        let nw = self.join(border, border, border, root.nw);
+        let ne = self.join(border, border, root.ne, border);
+        let sw = self.join(border, root.sw, border, border);
+        let se = self.join(root.se, border, border, border);
+        self.root = self.join(nw, ne, sw, se);
+    }
+
+    /// expand the root unless it is already the largest one
+    /// fails leaving the root as small as possible
+    fn grow(&mut self) -> Result<(), String> {
+        if self.level() == MAX_LEVEL {
+            self.shrink();
+            return Err("the pattern grew beyond the largest universe".to_string());
+        }
+        self.expand();
+        Ok(())
+    }
+
+    /// halve the size of the root while the pattern fits in its center
+    fn shrink(&mut self) {
+        while self.level() > MIN_ROOT_LEVEL && self.is_centered(self.root) {
+            self.root = self.center(self.root);
+        }
+    }
+
+    /// returns true if all the alive cells of the node are in its central half
+    fn is_centered(&self, id: NodeId) -> bool {
+        let node = self.nodes[id as usize];
+        let inner = [
+            self.nodes[node.nw as usize].se,
+            self.nodes[node.ne as usize].sw,
+            self.nodes[node.sw as usize].ne,
+            self.nodes[node.se as usize].nw,
+        ];
+        let inner_population: u64 = inner
+            .iter()
+            .map(|&child| self.nodes[child as usize].population)
+            .sum();
+        inner_population == node.population
+    }
+
+    /// the central half of a node
+    fn center(&mut self, id: NodeId) -> NodeId {
+        let node = self.nodes[id as usize];
+        let nw = self.nodes[node.nw as usize].se;
+        let ne = self.nodes[node.ne as usize].sw;
+        let sw = self.nodes[node.sw as usize].ne;
+        let se = self.nodes[node.se as usize].nw;
+        self.join(nw, ne, sw, se)
+    }
+
+    /// the central half of a node after 2^k generations
+    /// requires k <= level - 2
+    fn advance(&mut self, id: NodeId, k: u8) -> NodeId {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return self.empty_node(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, k)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.advance_base(id)
+        } else {
+            // 4x4 grid of grandchildren, from north west to south east
+            let [a, b, c, d] = [node.nw, node.ne, node.sw, node.se].map(|n| self.nodes[n as usize]);
+            let grid = [
+                [a.nw, a.ne, b.nw, b.ne],
+                [a.sw, a.se, b.sw, b.se],
+                [c.nw, c.ne, d.nw, d.ne],
+                [c.sw, c.se, d.sw, d.se],
+            ];
+
+            // advance the 9 overlapping subnodes by half of the generations
+            // or just take their center if the step is smaller
+            let full_step = k == node.level - 2;
+            let mut partial = [[DEAD; 3]; 3];
+            for i in 0..3 {
+                for j in 0..3 {
+                    let sub = self.join(
+                        grid[i][j],
+                        grid[i][j + 1],
+                        grid[i + 1][j],
+                        grid[i + 1][j + 1],
+                    );
+                    partial[i][j] = match full_step {
+                        true => self.advance(sub, k - 1),
+                        false => self.center(sub),
+                    };
+                }
+            }
+
+            // combine them in 4 nodes and advance the rest of the generations
+            let next_k = match full_step {
+                true => k - 1,
+                false => k,
+            };
+            let mut quadrants = [DEAD; 4];
+            for (q, (i, j)) in [(0, 0), (0, 1), (1, 0), (1, 1)].into_iter().enumerate() {
+                let sub = self.join(
+                    partial[i][j],
+                    partial[i][j + 1],
+                    partial[i + 1][j],
+                    partial[i + 1][j + 1],
+                );
+                quadrants[q] = self.advance(sub, next_k);
+            }
+            self.join(quadrants[0], quadrants[1], quadrants[2], quadrants[3])
+        };
+
+        self.results.insert((id, k), result);
+        result
+    }
+
+    /// one generation of the center 2x2 cells of a 4x4 node
+    fn advance_base(&mut self, id: NodeId) -> NodeId {
+        // cells[row][col], row 0 is the north edge
+        let mut cells = [[false; 4]; 4];
+        for (row, line) in cells.iter_mut().enumerate() {
+            for (col, cell) in line.iter_mut().enumerate() {
+                *cell = self.get_cell_in(id, col as i64, 3 - row as i64);
+            }
+        }
+
+        let mut next = [DEAD; 4];
+        for (i, (row, col)) in [(1, 1), (1, 2), (2, 1), (2, 2)].into_iter().enumerate() {
+            let neighbours = (row - 1..=row + 1)
+                .flat_map(|r| (col - 1..=col + 1).map(move |c| (r, c)))
+                .filter(|&(r, c)| (r, c) != (row, col) && cells[r][c])
+                .count() as u32;
+            let alive = match cells[row][col] {
+                true => self.rule.survives(neighbours),
+                false => self.rule.is_born(neighbours),
+            };
+            if alive {
+                next[i] = ALIVE;
+            }
+        }
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    /// get a cell of a node, the coords are relative to its south west corner
+    pub(crate) fn get_cell_in(&self, id: NodeId, x: i64, y: i64) -> bool {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return false;
+        }
+        if node.level == 0 {
+            return id == ALIVE;
+        }
+        let half = 1i64 << (node.level - 1);
+        match (x >= half, y >= half) {
+            (false, true) => self.get_cell_in(node.nw, x, y - half),
+            (true, true) => self.get_cell_in(node.ne, x - half, y - half),
+            (false, false) => self.get_cell_in(node.sw, x, y),
+            (true, false) => self.get_cell_in(node.se, x - half, y),
+        }
+    }
+
+    /// set a cell of a node, the coords are relative to its south west corner
+    pub(crate) fn set_cell_in(&mut self, id: NodeId, x: i64, y: i64, alive: bool) -> NodeId {
+        let node = self.nodes[id as usize];
+        if node.level == 0 {
+            return match alive {
+                true => ALIVE,
+                false => DEAD,
+            };
+        }
+        let half = 1i64 << (node.level - 1);
+        let (mut nw, mut ne, mut sw, mut se) = (node.nw, node.ne, node.sw, node.se);
+        match (x >= half, y >= half) {
+            (false, true) => nw = self.set_cell_in(nw, x, y - half, alive),
+            (true, true) => ne = self.set_cell_in(ne, x - half, y - half, alive),
+            (false, false) => sw = self.set_cell_in(sw, x, y, alive),
+            (true, false) => se = self.set_cell_in(se, x - half, y, alive),
+        }
+        self.join(nw, ne, sw, se)
+    }
+
+    /// add the alive cells of a node inside the area to the set
+    /// (x, y) is the south west corner of the node
+    fn collect_cells(
+        &self,
+        id: NodeId,
+        x: i64,
+        y: i64,
+        area: (i64, i64, i64, i64),
+        cells: &mut HashSet<(i32, i32)>,
+    ) {
+        let node = self.nodes[id as usize];
+        let size = 1i64 << node.level;
+        let (min_x, min_y, max_x, max_y) = area;
+        if node.population == 0 || x > max_x || y > max_y || x + size <= min_x || y + size <= min_y
+        {
+            return;
+        }
+        if node.level == 0 {
+            if let (Ok(x), Ok(y)) = (i32::try_from(x), i32::try_from(y)) {
+                cells.insert((x, y));
+            }
+            return;
+        }
+        let half = size / 2;
+        self.collect_cells(node.nw, x, y + half, area, cells);
+        self.collect_cells(node.ne, x + half, y + half, area, cells);
+        self.collect_cells(node.sw, x, y, area, cells);
+        self.collect_cells(node.se, x + half, y, area, cells);
+    }
+
+    /// area containing the alive cells of a node
+    /// (x, y) is the south west corner of the node
+    fn bounds(&self, id: NodeId, x: i64, y: i64) -> Option<(i64, i64, i64, i64)> {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            return Some((x, y, x, y));
+        }
+        let half = 1i64 << (node.level - 1);
+        [
+            self.bounds(node.nw, x, y + half),
+            self.bounds(node.ne, x + half, y + half),
+            self.bounds(node.sw, x, y),
+            self.bounds(node.se, x + half, y),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+    }
+
+    /// discard every node that is not reachable from the root
+    /// and the memoized results
+    fn collect_garbage(&mut self) {
+        let old_nodes = std::mem::take(&mut self.nodes);
+        self.nodes = old_nodes[..2].to_vec();
+        self.index.clear();
+        self.results.clear();
+        self.empty = vec![DEAD];
+
+        let mut copied = HashMap::new();
+        self.root = self.copy_node(&old_nodes, self.root, &mut copied);
+    }
+
+    fn copy_node(
+        &mut self,
+        old_nodes: &[Node],
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if id == DEAD || id == ALIVE {
+            return id;
+        }
+        if let Some(&new_id) = copied.get(&id) {
+            return new_id;
+        }
+        let node = old_nodes[id as usize];
+        let nw = self.copy_node(old_nodes, node.nw, copied);
+        let ne = self.copy_node(old_nodes, node.ne, copied);
+        let sw = self.copy_node(old_nodes, node.sw, copied);
+        let se = self.copy_node(old_nodes, node.se, copied);
+        let new_id = self.join(nw, ne, sw, se);
+        copied.insert(id, new_id);
+        new_id
+    }
+}
+
+/// convert a coord to i32, the ones outside of its range become its minimum or maximum
+fn saturate(value: i64) -> i32 {
+    value.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+impl Default for HashLife {
+    fn default() -> Self {
+        HashLife::new()
+    }
+}
+
+impl TryFrom<&GameOfLife> for HashLife {
+    type Error = String;
+
+    /// fails if the rule of the universe isn't Life-like
+    fn try_from(gol: &GameOfLife) -> Result<Self, Self::Error> {
+        let mut hashlife = HashLife::with_rule(gol.get_rule())?;
+        for &(x, y) in gol.get_alive_cells() {
+            hashlife.set_cell(x, y, true);
+        }
+        hashlife.step = gol.get_steps_count();
+        Ok(hashlife)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::model::soup::Soup;
+
+    fn glider() -> Vec<(i32, i32)> {
+        vec![(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]
+    }
+
+    #[test]
+    fn set_and_get_cells() {
+        let mut hashlife = HashLife::new();
+        assert!(!hashlife.get_cell(0, 0));
+        hashlife.change_cell_status(0, 0);
+        hashlife.change_cell_status(-100, 250);
+        assert!(hashlife.get_cell(0, 0));
+        assert!(hashlife.get_cell(-100, 250));
+        assert_eq!(hashlife.count_alive_cells(), 2);
+        hashlife.change_cell_status(0, 0);
+        assert!(!hashlife.get_cell(0, 0));
+        assert_eq!(hashlife.count_alive_cells(), 1);
+        assert_eq!(
+            hashlife.data_as_vec((-200, 0, 0, 300)),
+            HashSet::from([(-100, 250)])
+        );
+    }
+
+    #[test]
+    fn matches_game_of_life() {
+        let mut gol = GameOfLife::new();
+        gol.add_soup(&Soup::new(1, (-10, -10, 10, 10)));
+        let mut hashlife = HashLife::try_from(&gol).unwrap();
+
+        for _ in 0..20 {
+            gol.step();
+            hashlife.step();
+            let area = (-50, -50, 50, 50);
+            assert_eq!(gol.data_as_vec(area), hashlife.data_as_vec(area));
+        }
+        assert_eq!(hashlife.get_steps_count(), 20);
+    }
+
+    #[test]
+    fn glider_moves_after_many_generations() {
+        let mut hashlife = HashLife::new();
+        for (x, y) in glider() {
+            hashlife.set_cell(x, y, true);
+        }
+        // a glider moves one cell diagonally every 4 generations
+        hashlife.step_pow2(10).unwrap();
+        assert_eq!(hashlife.get_steps_count(), 1024);
+        assert_eq!(hashlife.count_alive_cells(), 5);
+        let expected: HashSet<(i32, i32)> = glider()
+            .into_iter()
+            .map(|(x, y)| (x + 256, y - 256))
+            .collect();
+        assert_eq!(hashlife.data_as_vec((0, -300, 300, 0)), expected);
+
+        hashlife.advance_generations(4 * 1000).unwrap();
+        let expected: HashSet<(i32, i32)> = glider()
+            .into_iter()
+            .map(|(x, y)| (x + 1256, y - 1256))
+            .collect();
+        assert_eq!(hashlife.data_as_vec((0, -2000, 2000, 0)), expected);
+    }
+
+    #[test]
+    fn glider_beyond_i32_coords() {
+        let mut hashlife = HashLife::new();
+        for (x, y) in glider() {
+            hashlife.set_cell(x, y, true);
+        }
+        // the glider moves 2^38 cells, far outside of the range of i32
+        hashlife.step_pow2(40).unwrap();
+        assert_eq!(hashlife.count_alive_cells(), 5);
+        assert_eq!(
+            hashlife.get_bounding_box(),
+            Some((i32::MAX, i32::MIN, i32::MAX, i32::MIN))
+        );
+        let area = (i32::MAX - 10, i32::MIN, i32::MAX, i32::MIN + 10);
+        assert!(hashlife.data_as_vec(area).is_empty());
+    }
+
+    #[test]
+    fn limits() {
+        let mut hashlife = HashLife::new();
+        for (x, y) in glider() {
+            hashlife.set_cell(x, y, true);
+        }
+        assert!(hashlife.step_pow2(64).is_err());
+        assert!(hashlife.step_pow2(MAX_STEP_POW2 + 1).is_err());
+        assert_eq!(hashlife.get_steps_count(), 0);
+
+        // the glider eventually reaches the edges of the largest universe
+        let mut steps = 0;
+        while hashlife.step_pow2(MAX_STEP_POW2).is_ok() {
+            steps += 1;
+            assert!(steps < 100);
+        }
+        assert_eq!(hashlife.get_steps_count(), steps << MAX_STEP_POW2);
+        assert_eq!(hashlife.count_alive_cells(), 5);
+        hashlife.step();
+        assert_eq!(hashlife.get_steps_count(), steps << MAX_STEP_POW2);
+
+        let mut hashlife = HashLife::new();
+        hashlife.set_cell(0, 0, true);
+        hashlife.step = u64::MAX;
+        assert!(hashlife.advance_generations(1).is_err());
+        assert_eq!(hashlife.get_steps_count(), u64::MAX);
+
+        assert!(HashLife::with_rule(Rule::parse("/2/3").unwrap()).is_err());
+        assert!(hashlife.set_rule(Rule::parse("B2/S34H").unwrap()).is_err());
+        assert_eq!(hashlife.get_rule(), Rule::conway());
+    }
+
+    #[test]
+    fn custom_rule() {
+        let rule = Rule::parse("B36/S23").unwrap();
+        let mut gol = GameOfLife::with_rule(rule);
+        gol.add_soup(&Soup::new(2, (-8, -8, 8, 8)));
+        let mut hashlife = HashLife::try_from(&gol).unwrap();
+        for _ in 0..8 {
+            gol.step();
+        }
+        hashlife.step_pow2(3).unwrap();
+        let area = (-40, -40, 40, 40);
+        assert_eq!(gol.data_as_vec(area), hashlife.data_as_vec(area));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// what produced a change in the universe
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// a step of the simulation
+    Generation,
+    /// cells added or removed by the user
+    Edit,
+}
+
+/// state of the whole universe
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub alive_cells: HashSet<(i32, i32)>,
+    pub cell_states: HashMap<(i32, i32), u8>,
+    pub step: u64,
+}
+
+/// cells changed by a generation, with their state before and after it
+/// step is the step counter before the generation
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diff {
+    pub cells: Vec<((i32, i32), u8, u8)>,
+    pub step: u64,
+}
+
+/// a change that can be undone
+/// generations only keep the cells they changed, so stepping doesn't copy the universe,
+/// edits keep the whole universe since they can change any number of cells
+#[derive(Clone, Debug, PartialEq)]
+pub enum Entry {
+    Generation(Diff),
+    Edit(Snapshot),
+}
+
+impl Entry {
+    pub fn get_change(&self) -> Change {
+        match self {
+            Entry::Generation(_) => Change::Generation,
+            Entry::Edit(_) => Change::Edit,
+        }
+    }
+}
+
+/// bounded list of the previous changes of the universe
+/// undone changes are kept until a new change is recorded so they can be redone
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    limit: usize,
+}
+
+impl History {
+    /// create a history keeping at most `limit` changes
+    /// a limit of 0 disables it
+    pub fn new(limit: usize) -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit,
+        }
+    }
+
+    /// save a new change, the oldest change is dropped if the history is full
+    pub fn record(&mut self, entry: Entry) {
+        if self.limit == 0 {
+            return;
+        }
+        self.redo.clear();
+        if self.undo.len() == self.limit {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(entry);
+    }
+
+    /// take the last change to undo it
+    /// the caller gives it back with `keep_redo` once undone
+    pub fn undo(&mut self) -> Option<Entry> {
+        self.undo.pop_back()
+    }
+
+    /// take the last undone change to apply it again
+    /// the caller gives it back with `keep_undo` once applied
+    pub fn redo(&mut self) -> Option<Entry> {
+        self.redo.pop()
+    }
+
+    /// keep an undone change so it can be redone
+    pub fn keep_redo(&mut self, entry: Entry) {
+        self.redo.push(entry);
+    }
+
+    /// keep a redone change so it can be undone again
+    pub fn keep_undo(&mut self, entry: Entry) {
+        self.undo.push_back(entry);
+    }
+
+    /// get the change that the next undo reverts, if any
+    pub fn last_change(&self) -> Option<Change> {
+        self.undo.back().map(Entry::get_change)
+    }
+
+    /// returns true if any of the changes that can be undone is of the given kind
+    pub fn contains(&self, change: Change) -> bool {
+        self.undo.iter().any(|entry| entry.get_change() == change)
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        while self.undo.len() > limit {
+            self.undo.pop_front();
+        }
+        if limit == 0 {
+            self.redo.clear();
+        }
+    }
+
+    pub fn get_limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn generation(step: u64) -> Entry {
+        Entry::Generation(Diff {
+            cells: vec![((step as i32, 0), 0, 1)],
+            step,
+        })
+    }
+
+    fn edit(step: u64) -> Entry {
+        Entry::Edit(Snapshot {
+            alive_cells: HashSet::from([(step as i32, 0)]),
+            cell_states: HashMap::new(),
+            step,
+        })
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = History::new(10);
+        history.record(edit(0));
+        history.record(generation(1));
+        assert_eq!(history.last_change(), Some(Change::Generation));
+        assert!(history.contains(Change::Edit));
+
+        let previous = history.undo().unwrap();
+        assert_eq!(previous, generation(1));
+        assert_eq!(history.last_change(), Some(Change::Edit));
+        history.keep_redo(previous);
+        let next = history.redo().unwrap();
+        assert_eq!(next, generation(1));
+        history.keep_undo(next);
+        assert_eq!(history.last_change(), Some(Change::Generation));
+
+        // a new change discards the undone changes
+        let previous = history.undo().unwrap();
+        history.keep_redo(previous);
+        history.record(edit(1));
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn limit() {
+        let mut history = History::new(2);
+        for step in 0..5 {
+            history.record(generation(step));
+        }
+        assert_eq!(history.undo(), Some(generation(4)));
+        assert_eq!(history.undo(), Some(generation(3)));
+        assert!(history.undo().is_none());
+
+        let mut history = History::new(0);
+        history.record(edit(0));
+        assert_eq!(history.last_change(), None);
+    }
+}
@@ -0,0 +1,765 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::rule_table::{self, RuleTable};
+
+mod hensel;
+
+pub use hensel::Table;
+
+/// maximum number of states of a Generations rule
+pub const MAX_STATES: u32 = 255;
+
+/// maximum range of the neighbourhood of Larger than Life rules
+pub const MAX_RANGE: u32 = 10;
+
+/// shape of the cells around a cell that count as its neighbours
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Neighbourhood {
+    /// the square around the cell
+    #[default]
+    Moore,
+    /// the diamond of cells at a manhattan distance up to the range
+    VonNeumann,
+    /// the cells at an euclidean distance up to the range plus one half
+    Circular,
+    /// the hexagon of cells seen in a grid whose rows are skewed half a cell,
+    /// the square without its top right and bottom left corners
+    Hexagonal,
+}
+
+impl Neighbourhood {
+    /// get the first and last columns, relative to the cell, of the neighbours in the row dy
+    pub fn get_row(self, range: i32, dy: i32) -> (i32, i32) {
+        let width = match self {
+            Neighbourhood::Moore => range,
+            Neighbourhood::VonNeumann => range - dy.abs(),
+            Neighbourhood::Circular => (range * range + range - dy * dy).isqrt(),
+            Neighbourhood::Hexagonal => return ((-range).max(-range - dy), range.min(range - dy)),
+        };
+        (-width, width)
+    }
+
+    /// get the positions of the neighbours relative to the cell, without the cell
+    pub fn get_offsets(self, range: i32) -> Vec<(i32, i32)> {
+        let mut offsets = Vec::new();
+        for dy in -range..=range {
+            let (first, last) = self.get_row(range, dy);
+            for dx in first..=last {
+                if (dx, dy) != (0, 0) {
+                    offsets.push((dx, dy));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+/// birth and survival counts of Larger than Life rules, from min to max
+/// middle is true if an alive cell counts itself to survive
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Ranges {
+    birth: (u32, u32),
+    survival: (u32, u32),
+    middle: bool,
+}
+
+/// Life-like rule in B/S notation
+/// bit n of birth is set if a dead cell with n alive neighbours is born
+/// bit n of survival is set if an alive cell with n alive neighbours survives
+/// states counts the dead and alive states plus the decay states of Generations rules,
+/// an alive cell that doesn't survive goes through the decay states before dying
+/// Larger than Life rules have a range above 1 and use ranges of counts instead
+/// and non-totalistic rules, whose next states depend on where the neighbours are, use a table
+/// rules of rule tables keep their table
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+    states: u8,
+    range: u8,
+    neighbourhood: Neighbourhood,
+    ranges: Option<Ranges>,
+    table: Option<Table>,
+    rule_table: Option<Arc<RuleTable>>,
+}
+
+impl Rule {
+    /// create a rule from the neighbour counts that cause a birth or a survival
+    pub fn new(birth: &[u32], survival: &[u32]) -> Result<Self, RuleError> {
+        let mut rule = Rule {
+            birth: 0,
+            survival: 0,
+            states: 2,
+            range: 1,
+            neighbourhood: Neighbourhood::Moore,
+            ranges: None,
+            table: None,
+            rule_table: None,
+        };
+        for &n in birth {
+            if n > 8 {
+                return Err(RuleError::InvalidNeighbourCount(n));
+            }
+            rule.birth |= 1 << n;
+        }
+        for &n in survival {
+            if n > 8 {
+                return Err(RuleError::InvalidNeighbourCount(n));
+            }
+            rule.survival |= 1 << n;
+        }
+        if rule.birth & 1 != 0 {
+            return Err(RuleError::BirthOnZero);
+        }
+        Ok(rule)
+    }
+
+    /// create a Generations rule with the given number of states
+    /// 2 states is a Life-like rule, with more the cells decay before dying
+    pub fn generations(birth: &[u32], survival: &[u32], states: u32) -> Result<Self, RuleError> {
+        if !(2..=MAX_STATES).contains(&states) {
+            return Err(RuleError::InvalidStates(states));
+        }
+        Ok(Rule {
+            states: states as u8,
+            ..Rule::new(birth, survival)?
+        })
+    }
+
+    /// create a Larger than Life rule, the counts go from min to max inclusive
+    /// with middle, an alive cell counts itself to survive
+    /// rules of range 1 are turned into Life-like or Generations rules
+    pub fn larger_than_life(
+        range: u32,
+        neighbourhood: Neighbourhood,
+        middle: bool,
+        birth: (u32, u32),
+        survival: (u32, u32),
+        states: u32,
+    ) -> Result<Self, RuleError> {
+        if !(1..=MAX_RANGE).contains(&range) {
+            return Err(RuleError::InvalidRange(range));
+        }
+        // the cell itself can be counted to survive
+        let neighbours = neighbourhood.get_offsets(range as i32).len() as u32;
+        for ((min, max), limit) in [(birth, neighbours), (survival, neighbours + middle as u32)] {
+            if max > limit {
+                return Err(RuleError::InvalidNeighbourCount(max));
+            }
+            if min > max {
+                return Err(RuleError::InvalidNeighbourCount(min));
+            }
+        }
+        if birth.0 == 0 {
+            return Err(RuleError::BirthOnZero);
+        }
+
+        if range == 1 && neighbourhood != Neighbourhood::Circular {
+            let survival: Vec<u32> = (survival.0..=survival.1)
+                .filter_map(|n| n.checked_sub(middle as u32))
+                .collect();
+            let birth: Vec<u32> = (birth.0..=birth.1.min(8)).collect();
+            return Rule::generations(&birth, &survival, states)?.with_neighbourhood(neighbourhood);
+        }
+        Ok(Rule {
+            range: range as u8,
+            neighbourhood,
+            ranges: Some(Ranges {
+                birth,
+                survival,
+                middle,
+            }),
+            ..Rule::generations(&[], &[], states)?
+        })
+    }
+
+    /// use the von Neumann or the hexagonal neighbourhood instead of the 8 neighbours around the cell
+    /// only for totalistic rules of range 1, with counts up to the number of neighbours
+    pub fn with_neighbourhood(self, neighbourhood: Neighbourhood) -> Result<Self, RuleError> {
+        if self.range != 1 || !self.is_totalistic() {
+            return Err(RuleError::InvalidFormat(self.to_string()));
+        }
+        let neighbours = neighbourhood.get_offsets(1).len() as u32;
+        if let Some(n) = (neighbours + 1..=8).find(|&n| self.is_born(n) || self.survives(n)) {
+            return Err(RuleError::InvalidNeighbourCount(n));
+        }
+        Ok(Rule {
+            neighbourhood,
+            ..self
+        })
+    }
+
+    /// create a rule from the next state of every 3x3 block
+    /// tables that only depend on the number of neighbours are turned into Life-like rules
+    pub fn from_table(table: Table, states: u32) -> Result<Self, RuleError> {
+        if table.is_alive(0) {
+            return Err(RuleError::BirthOnZero);
+        }
+        let rule = Rule::generations(&[], &[], states)?;
+        Ok(match table.get_counts() {
+            Some((birth, survival)) => Rule {
+                birth,
+                survival,
+                ..rule
+            },
+            None => Rule {
+                table: Some(table),
+                ..rule
+            },
+        })
+    }
+
+    /// create the rule of a rule table
+    pub fn from_rule_table(table: Arc<RuleTable>) -> Self {
+        Rule {
+            birth: 0,
+            survival: 0,
+            states: table.get_states(),
+            range: 1,
+            neighbourhood: table.get_neighbourhood(),
+            ranges: None,
+            table: None,
+            rule_table: Some(table),
+        }
+    }
+
+    /// the standard Conway's game of life rule, B3/S23
+    pub fn conway() -> Self {
+        Rule {
+            birth: 1 << 3,
+            survival: (1 << 2) | (1 << 3),
+            states: 2,
+            range: 1,
+            neighbourhood: Neighbourhood::Moore,
+            ranges: None,
+            table: None,
+            rule_table: None,
+        }
+    }
+
+    /// parse a rulestring
+    /// accepts the B/S notation ("B3/S23", "B36/S23", "B2/S")
+    /// and the S/B notation ("23/3", "/2")
+    /// Generations rules add the number of states ("B2/S/C3", "345/2/4")
+    /// isotropic non-totalistic rules add letters after the counts ("B2-a/S12", "B3/S23-a4i")
+    /// and any rule of the 8 neighbours can be written in the MAP notation ("MAPARYXfhZofugWaH7oaID...")
+    /// the B/S and S/B notations end with H for the hexagonal neighbourhood ("B2/S34H")
+    /// and V for the von Neumann neighbourhood ("B1/S1V")
+    /// the rules of the registered rule tables are named by the table ("Wireworld"),
+    /// the tables are global to the process, see rule_table::register
+    /// and Larger than Life rules use the "R5,C0,M1,S34..58,B34..45,NM" notation
+    pub fn parse(rulestring: &str) -> Result<Self, RuleError> {
+        let rulestring = rulestring.trim();
+        // the registered tables are only looked up for the names that aren't rulestrings
+        Rule::parse_notation(rulestring).or_else(|e| rule_table::find(rulestring).ok_or(e))
+    }
+
+    /// parse a rulestring in any of the notations, without the names of the rule tables
+    fn parse_notation(rulestring: &str) -> Result<Self, RuleError> {
+        if rulestring.is_empty() {
+            return Err(RuleError::Empty);
+        }
+        if prefix(rulestring) == Some('R') && rulestring.contains(',') {
+            return parse_larger_than_life(rulestring);
+        }
+        if let Some(map) = rulestring.strip_prefix("MAP") {
+            let (digits, states) = map.split_at(map.len().min(86));
+            let states = match states.trim_start_matches('=').strip_prefix('/') {
+                Some(states) => parse_states(states, rulestring)?,
+                None if states.trim_start_matches('=').is_empty() => 2,
+                None => return Err(RuleError::InvalidFormat(rulestring.to_string())),
+            };
+            return Rule::from_table(Table::parse_map(digits)?, states);
+        }
+        let neighbourhood = match rulestring.chars().last().map(|c| c.to_ascii_uppercase()) {
+            Some('H') => Some(Neighbourhood::Hexagonal),
+            Some('V') => Some(Neighbourhood::VonNeumann),
+            _ => None,
+        };
+        if let Some(neighbourhood) = neighbourhood {
+            let rule = Rule::parse_notation(&rulestring[..rulestring.len() - 1])?;
+            return rule.with_neighbourhood(neighbourhood);
+        }
+
+        let parts: Vec<&str> = rulestring.split('/').collect();
+        let states = match parts.len() {
+            2 => 2,
+            3 => parse_states(parts[2], rulestring)?,
+            _ => return Err(RuleError::InvalidFormat(rulestring.to_string())),
+        };
+
+        let (birth, survival) = match (prefix(parts[0]), prefix(parts[1])) {
+            (Some('B'), Some('S')) => (&parts[0][1..], &parts[1][1..]),
+            (Some('S'), Some('B')) => (&parts[1][1..], &parts[0][1..]),
+            // S/B notation without letters
+            (None, None) => (parts[1], parts[0]),
+            _ => return Err(RuleError::InvalidFormat(rulestring.to_string())),
+        };
+
+        if !(birth.chars().chain(survival.chars())).all(|c| c.is_ascii_digit()) {
+            return Rule::from_table(Table::parse_hensel(birth, survival)?, states);
+        }
+        Rule::generations(&parse_counts(birth)?, &parse_counts(survival)?, states)
+    }
+
+    /// returns true if a dead cell with `neighbours` alive neighbours is born
+    /// always false for non-totalistic rules
+    pub fn is_born(&self, neighbours: u32) -> bool {
+        match self.ranges {
+            Some(ranges) => (ranges.birth.0..=ranges.birth.1).contains(&neighbours),
+            None => neighbours <= 8 && self.birth & (1 << neighbours) != 0,
+        }
+    }
+
+    /// returns true if an alive cell with `neighbours` alive neighbours survives
+    /// the cell itself is added by the Larger than Life rules that count the middle
+    pub fn survives(&self, neighbours: u32) -> bool {
+        match self.ranges {
+            Some(ranges) => (ranges.survival.0..=ranges.survival.1)
+                .contains(&(neighbours + ranges.middle as u32)),
+            None => neighbours <= 8 && self.survival & (1 << neighbours) != 0,
+        }
+    }
+
+    /// returns true if the cell in the middle of a 3x3 block is alive in the next generation
+    /// bit n of the block is the cell in the row n / 3 and the column n % 3 from the top left,
+    /// the cell is bit 4, only for rules with the 8 neighbours of the Moore neighbourhood
+    pub fn is_alive_next(&self, block: usize) -> bool {
+        match self.table {
+            Some(table) => table.is_alive(block),
+            None => {
+                let neighbours = (block & 0b111_101_111).count_ones();
+                match block & 1 << 4 != 0 {
+                    true => self.survives(neighbours),
+                    false => self.is_born(neighbours),
+                }
+            }
+        }
+    }
+
+    /// returns true if the next state of a cell only depends on how many neighbours are alive
+    pub fn is_totalistic(&self) -> bool {
+        self.table.is_none() && self.rule_table.is_none()
+    }
+
+    /// get the table of the rules given by a rule table
+    pub fn get_rule_table(&self) -> Option<Arc<RuleTable>> {
+        self.rule_table.clone()
+    }
+
+    /// get how far the neighbours of a cell can be, 1 except in Larger than Life rules
+    pub fn get_range(&self) -> u32 {
+        self.range as u32
+    }
+
+    pub fn get_neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    /// get the positions of the neighbours relative to a cell
+    /// in the order of the transitions for the rules of rule tables
+    pub fn get_neighbour_offsets(&self) -> Vec<(i32, i32)> {
+        match self.get_rule_table() {
+            Some(table) => table.get_offsets(),
+            None => self.neighbourhood.get_offsets(self.range as i32),
+        }
+    }
+
+    /// returns true if the rule is totalistic with two states and the 8 neighbours of the Moore neighbourhood,
+    /// the only rules simulated by every engine
+    pub fn is_life_like(&self) -> bool {
+        self.states == 2
+            && self.range == 1
+            && self.neighbourhood == Neighbourhood::Moore
+            && self.is_totalistic()
+    }
+
+    /// get the number of states, 2 for Life-like rules
+    pub fn get_states(&self) -> u8 {
+        self.states
+    }
+
+    /// returns true if the cells decay through more states than alive and dead
+    pub fn is_generations(&self) -> bool {
+        self.states > 2
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::parse(s)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(table) = self.get_rule_table() {
+            return write!(f, "{}", table.get_name());
+        }
+        if let Some(ranges) = self.ranges {
+            let neighbourhood = match self.neighbourhood {
+                Neighbourhood::Moore => 'M',
+                Neighbourhood::VonNeumann => 'N',
+                Neighbourhood::Circular => 'C',
+                Neighbourhood::Hexagonal => 'H',
+            };
+            return write!(
+                f,
+                "R{},C{},M{},S{}..{},B{}..{},N{}",
+                self.range,
+                match self.is_generations() {
+                    true => self.states,
+                    false => 0,
+                },
+                ranges.middle as u8,
+                ranges.survival.0,
+                ranges.survival.1,
+                ranges.birth.0,
+                ranges.birth.1,
+                neighbourhood
+            );
+        }
+        if let Some(table) = self.table {
+            match table.is_isotropic() {
+                true => {
+                    let (birth, survival) = table.to_hensel();
+                    write!(f, "B{}/S{}", birth, survival)?;
+                }
+                false => write!(f, "MAP{}", table.to_map())?,
+            }
+            if self.is_generations() {
+                write!(f, "/C{}", self.states)?;
+            }
+            return Ok(());
+        }
+        write!(f, "B")?;
+        for n in 0..=8 {
+            if self.is_born(n) {
+                write!(f, "{}", n)?;
+            }
+        }
+        write!(f, "/S")?;
+        for n in 0..=8 {
+            if self.survives(n) {
+                write!(f, "{}", n)?;
+            }
+        }
+        if self.is_generations() {
+            write!(f, "/C{}", self.states)?;
+        }
+        match self.neighbourhood {
+            Neighbourhood::Hexagonal => write!(f, "H"),
+            Neighbourhood::VonNeumann => write!(f, "V"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// errors found while parsing a rulestring
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    Empty,
+    InvalidFormat(String),
+    InvalidDigit(char),
+    InvalidNeighbourCount(u32),
+    BirthOnZero,
+    InvalidStates(u32),
+    InvalidRange(u32),
+    /// the letter isn't a configuration of the number of neighbours
+    InvalidLetter(u32, char),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::Empty => write!(f, "empty rulestring"),
+            RuleError::InvalidFormat(s) => write!(f, "invalid rulestring '{}'", s),
+            RuleError::InvalidDigit(c) => write!(f, "invalid neighbour count '{}'", c),
+            RuleError::InvalidNeighbourCount(n) => {
+                write!(f, "neighbour count {} out of range", n)
+            }
+            RuleError::BirthOnZero => write!(f, "rules with B0 are not supported"),
+            RuleError::InvalidStates(n) => {
+                write!(f, "number of states {} out of range 2-{}", n, MAX_STATES)
+            }
+            RuleError::InvalidRange(n) => {
+                write!(f, "neighbourhood range {} out of range 1-{}", n, MAX_RANGE)
+            }
+            RuleError::InvalidLetter(n, c) => {
+                write!(f, "invalid letter '{}' for {} neighbours", c, n)
+            }
+        }
+    }
+}
+
+impl Error for RuleError {}
+
+/// get the uppercase letter at the start of a rule part, if any
+fn prefix(part: &str) -> Option<char> {
+    part.chars()
+        .next()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+}
+
+/// parse the number of states of a Generations rule, like "C3", "G3" or "3"
+fn parse_states(part: &str, rulestring: &str) -> Result<u32, RuleError> {
+    let states = match prefix(part) {
+        Some('C') | Some('G') => &part[1..],
+        None => part,
+        _ => return Err(RuleError::InvalidFormat(rulestring.to_string())),
+    };
+    states
+        .parse()
+        .map_err(|_| RuleError::InvalidFormat(rulestring.to_string()))
+}
+
+/// parse a Larger than Life rule like "R5,C0,M1,S34..58,B34..45,NM"
+/// C, M and N can be left out for 2 states, not counting the middle and the Moore neighbourhood
+fn parse_larger_than_life(rulestring: &str) -> Result<Rule, RuleError> {
+    let invalid = || RuleError::InvalidFormat(rulestring.to_string());
+    let number = |value: &str| value.parse::<u32>().map_err(|_| invalid());
+    let counts = |value: &str| -> Result<(u32, u32), RuleError> {
+        match value.split_once("..") {
+            Some((min, max)) => Ok((number(min)?, number(max)?)),
+            None => number(value).map(|n| (n, n)),
+        }
+    };
+
+    let (mut range, mut states, mut middle) = (None, 0, false);
+    let (mut birth, mut survival) = (None, None);
+    let mut neighbourhood = Neighbourhood::Moore;
+    for part in rulestring.split(',') {
+        let part = part.trim();
+        let value = part.get(1..).ok_or_else(invalid)?;
+        match prefix(part) {
+            Some('R') => range = Some(number(value)?),
+            Some('C') => states = number(value)?,
+            Some('M') => {
+                middle = match value {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err(invalid()),
+                }
+            }
+            Some('S') => survival = Some(counts(value)?),
+            Some('B') => birth = Some(counts(value)?),
+            Some('N') => {
+                neighbourhood = match value.to_uppercase().as_str() {
+                    "M" => Neighbourhood::Moore,
+                    "N" => Neighbourhood::VonNeumann,
+                    "C" => Neighbourhood::Circular,
+                    "H" => Neighbourhood::Hexagonal,
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    let (Some(range), Some(birth), Some(survival)) = (range, birth, survival) else {
+        return Err(invalid());
+    };
+    // C0 and C1 are used for 2 states
+    Rule::larger_than_life(range, neighbourhood, middle, birth, survival, states.max(2))
+}
+
+fn parse_counts(digits: &str) -> Result<Vec<u32>, RuleError> {
+    digits
+        .chars()
+        .map(|c| c.to_digit(10).ok_or(RuleError::InvalidDigit(c)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_bs_notation() {
+        let rule = Rule::parse("B3/S23").unwrap();
+        assert_eq!(rule, Rule::conway());
+        assert!(rule.is_born(3));
+        assert!(!rule.is_born(2));
+        assert!(rule.survives(2));
+        assert!(rule.survives(3));
+        assert!(!rule.survives(4));
+
+        assert_eq!(Rule::parse("b36/s23").unwrap().to_string(), "B36/S23");
+        assert_eq!(Rule::parse("S23/B3").unwrap(), Rule::conway());
+        assert_eq!(Rule::parse("B2/S").unwrap().to_string(), "B2/S");
+    }
+
+    #[test]
+    fn parse_sb_notation() {
+        assert_eq!(Rule::parse("23/3").unwrap(), Rule::conway());
+        assert_eq!(
+            Rule::parse("34678/3678").unwrap().to_string(),
+            "B3678/S34678"
+        );
+        assert_eq!(Rule::parse("/2").unwrap().to_string(), "B2/S");
+    }
+
+    #[test]
+    fn parse_generations() {
+        // Brian's Brain
+        let rule = Rule::parse("/2/3").unwrap();
+        assert_eq!(rule.get_states(), 3);
+        assert!(rule.is_born(2));
+        assert_eq!(rule.to_string(), "B2/S/C3");
+        assert_eq!(Rule::parse("B2/S/C3").unwrap(), rule);
+        assert_eq!(Rule::parse("b2/s/g3").unwrap(), rule);
+
+        // Star Wars
+        let rule = Rule::parse("345/2/4").unwrap();
+        assert_eq!(rule.to_string(), "B2/S345/C4");
+        assert_eq!(rule.to_string().parse(), Ok(rule));
+
+        // two states is a Life-like rule
+        assert_eq!(Rule::parse("23/3/2").unwrap(), Rule::conway());
+        assert_eq!(Rule::parse("/2/1"), Err(RuleError::InvalidStates(1)));
+        assert_eq!(Rule::parse("/2/256"), Err(RuleError::InvalidStates(256)));
+        assert!(Rule::parse("B2/S/X3").is_err());
+    }
+
+    #[test]
+    fn parse_larger_than_life() {
+        // Bosco's rule
+        let rule = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+        assert_eq!(rule.get_range(), 5);
+        assert_eq!(rule.get_neighbourhood(), Neighbourhood::Moore);
+        assert_eq!(rule.get_neighbour_offsets().len(), 120);
+        assert!(rule.is_born(34));
+        assert!(!rule.is_born(46));
+        // the cell counts itself to survive
+        assert!(rule.survives(33));
+        assert!(!rule.survives(58));
+        assert!(!rule.is_life_like());
+        assert_eq!(rule.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+        assert_eq!(rule.to_string().parse(), Ok(rule));
+
+        let rule = Rule::parse("r3,c4,s2..3,b3,nn").unwrap();
+        assert_eq!(rule.get_states(), 4);
+        assert_eq!(rule.get_neighbour_offsets().len(), 24);
+        assert_eq!(rule.to_string(), "R3,C4,M0,S2..3,B3..3,NN");
+        let rule = Rule::parse("R2,C0,M0,S1..5,B2..4,NC").unwrap();
+        assert_eq!(rule.get_neighbour_offsets().len(), 20);
+
+        // range 1 is Life-like
+        assert_eq!(
+            Rule::parse("R1,C0,M1,S3..4,B3..3,NM").unwrap(),
+            Rule::conway()
+        );
+        assert_eq!(
+            Rule::parse("R11,C0,M0,S1..2,B3..3,NM"),
+            Err(RuleError::InvalidRange(11))
+        );
+        assert_eq!(
+            Rule::parse("R1,C0,M0,S1..9,B3..3,NM"),
+            Err(RuleError::InvalidNeighbourCount(9))
+        );
+        assert_eq!(
+            Rule::parse("R2,C0,M0,S1..2,B0..3,NM"),
+            Err(RuleError::BirthOnZero)
+        );
+        assert!(Rule::parse("R2,C0,M0,S1..2,NM").is_err());
+        assert!(Rule::parse("R2,C0,M2,S1..2,B3,NM").is_err());
+    }
+
+    #[test]
+    fn parse_neighbourhood_suffixes() {
+        let rule = Rule::parse("B2/S34H").unwrap();
+        assert_eq!(rule.get_neighbourhood(), Neighbourhood::Hexagonal);
+        assert_eq!(rule.get_neighbour_offsets().len(), 6);
+        assert!(!rule.is_life_like());
+        assert_eq!(rule.to_string(), "B2/S34H");
+        assert_eq!(Rule::parse("34/2h"), Ok(rule.clone()));
+        assert_eq!(Rule::parse("R1,C0,M0,S3..4,B2,NH"), Ok(rule));
+
+        let rule = Rule::parse("B1/S1/C3V").unwrap();
+        assert_eq!(rule.get_neighbourhood(), Neighbourhood::VonNeumann);
+        assert_eq!(rule.get_states(), 3);
+        assert_eq!(rule.to_string(), "B1/S1/C3V");
+
+        let rule = Rule::parse("R3,C0,M1,S2..9,B3..5,NH").unwrap();
+        assert_eq!(rule.get_neighbour_offsets().len(), 36);
+        assert_eq!(rule.to_string(), "R3,C0,M1,S2..9,B3..5,NH");
+
+        assert_eq!(
+            Rule::parse("B7/S2H"),
+            Err(RuleError::InvalidNeighbourCount(7))
+        );
+        assert_eq!(
+            Rule::parse("B2/S5V"),
+            Err(RuleError::InvalidNeighbourCount(5))
+        );
+        assert!(Rule::parse("B2a/S2H").is_err());
+        assert_eq!(Rule::parse("H"), Err(RuleError::Empty));
+    }
+
+    #[test]
+    fn parse_hensel_notation() {
+        let rule = Rule::parse("B2-a/S12").unwrap();
+        assert!(!rule.is_totalistic());
+        assert!(!rule.is_life_like());
+        assert_eq!(rule.to_string(), "B2-a/S12");
+        assert_eq!(Rule::parse("B2cekin/S12"), Ok(rule.clone()));
+        assert_eq!(Rule::parse("12/2-a"), Ok(rule));
+        assert_eq!(
+            Rule::parse("B3/S2-a34iz/C4").unwrap().to_string(),
+            "B3/S2-a34iz/C4"
+        );
+
+        // every letter of a count is the same as the count alone
+        assert_eq!(Rule::parse("B3/S2cekain3"), Ok(Rule::conway()));
+
+        // a rule that moves every cell down isn't isotropic
+        let down = format!("MAP{}I{}", "A".repeat(21), "A".repeat(64));
+        let rule = Rule::parse(&down).unwrap();
+        assert_eq!(rule.to_string(), down);
+        assert!(rule.is_alive_next(1 << 1));
+        assert!(!rule.is_alive_next(1 << 7));
+        assert_eq!(
+            Rule::parse(&format!("{}==/C3", down)).unwrap().get_states(),
+            3
+        );
+
+        let conway = "MAPARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA";
+        assert_eq!(Rule::parse(conway), Ok(Rule::conway()));
+
+        // tlife, a non-totalistic rule, and its MAP string
+        let tlife = "MAPARYXfhZofmgWaH7oaIDqgBZoXuhogOiAaIDoiIAAgAAWaH74aIDogGiA6ICAAIAAaIDogIAAwACAAIAAAAAAAA";
+        assert_eq!(Rule::parse(tlife), Rule::parse("B3/S2-i34q"));
+
+        assert_eq!(Rule::parse("B2x/S"), Err(RuleError::InvalidLetter(2, 'x')));
+        assert_eq!(Rule::parse("B3/S8c"), Err(RuleError::InvalidLetter(8, 'c')));
+        assert_eq!(Rule::parse("B0/S2a"), Err(RuleError::BirthOnZero));
+        assert!(Rule::parse("MAPARYX").is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Rule::parse(""), Err(RuleError::Empty));
+        assert_eq!(
+            Rule::parse("B3S23"),
+            Err(RuleError::InvalidFormat("B3S23".to_string()))
+        );
+        assert_eq!(Rule::parse("B3/S2!"), Err(RuleError::InvalidDigit('!')));
+        assert_eq!(
+            Rule::parse("B39/S23"),
+            Err(RuleError::InvalidNeighbourCount(9))
+        );
+        assert_eq!(Rule::parse("B03/S23"), Err(RuleError::BirthOnZero));
+    }
+}
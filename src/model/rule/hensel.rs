@@ -0,0 +1,356 @@
+use super::super::transform::Transform;
+use super::RuleError;
+
+/// bits of the 8 neighbours in a block, every bit but the cell
+const NEIGHBOURS: usize = 0b111_101_111;
+
+/// bit of the cell in the middle of a block
+const CELL: usize = 1 << 4;
+
+/// base64 digits of the MAP notation
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// number of base64 digits of a MAP rule, 512 bits in groups of 6
+const MAP_LENGTH: usize = 86;
+
+/// a block of the neighbours of each letter of 1 to 4 alive neighbours, in the order they are written
+/// the other blocks of a letter are its rotations and reflections,
+/// and 5 to 7 neighbours use the letters of the dead neighbours
+const REPRESENTATIVES: [&[(char, usize)]; 5] = [
+    &[],
+    &[('c', 1), ('e', 2)],
+    &[
+        ('c', 5),
+        ('e', 10),
+        ('k', 33),
+        ('a', 3),
+        ('i', 40),
+        ('n', 68),
+    ],
+    &[
+        ('c', 69),
+        ('e', 42),
+        ('k', 98),
+        ('a', 11),
+        ('i', 7),
+        ('n', 13),
+        ('y', 97),
+        ('q', 70),
+        ('j', 14),
+        ('r', 41),
+    ],
+    &[
+        ('c', 325),
+        ('e', 170),
+        ('k', 99),
+        ('a', 15),
+        ('i', 45),
+        ('n', 71),
+        ('y', 78),
+        ('q', 102),
+        ('j', 106),
+        ('r', 43),
+        ('t', 101),
+        ('w', 105),
+        ('z', 108),
+    ],
+];
+
+/// next state of the cell in the middle of every 3x3 block of cells
+/// bit n of a block is the cell in the row n / 3 and the column n % 3 from the top left,
+/// so the cell is bit 4 and its neighbours are the other bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Table([u64; 8]);
+
+impl Table {
+    /// the table of a totalistic rule, bit n of birth and survival as in `Rule`
+    pub fn from_counts(birth: u16, survival: u16) -> Self {
+        let mut table = Table([0; 8]);
+        for block in 0..512 {
+            let counts = match block & CELL != 0 {
+                true => survival,
+                false => birth,
+            };
+            if counts >> count(block) & 1 == 1 {
+                table.set_alive(block);
+            }
+        }
+        table
+    }
+
+    /// parse the birth and survival parts of a rulestring in Hensel notation without the B and S,
+    /// each count is followed by the letters of its configurations or a - and the letters left out,
+    /// like "2-a" and "12"
+    pub fn parse_hensel(birth: &str, survival: &str) -> Result<Self, RuleError> {
+        let mut table = Table([0; 8]);
+        for (part, cell) in [(birth, 0), (survival, CELL)] {
+            for neighbours in parse_configurations(part)? {
+                table.set_alive(neighbours | cell);
+            }
+        }
+        Ok(table)
+    }
+
+    /// parse the base64 digits after MAP, the bits of each block in order from the block 0,
+    /// whose bits are read from the top left cell down to the bottom right
+    pub fn parse_map(digits: &str) -> Result<Self, RuleError> {
+        if digits.len() != MAP_LENGTH {
+            return Err(RuleError::InvalidFormat(format!("MAP{}", digits)));
+        }
+        let mut table = Table([0; 8]);
+        let mut index = 0;
+        for c in digits.chars() {
+            let value = BASE64
+                .iter()
+                .position(|&d| d as char == c)
+                .ok_or(RuleError::InvalidDigit(c))?;
+            for bit in (0..6).rev() {
+                if index < 512 && value >> bit & 1 == 1 {
+                    table.set_alive(reverse(index));
+                }
+                index += 1;
+            }
+        }
+        Ok(table)
+    }
+
+    /// returns true if the cell in the middle of the block is alive in the next generation
+    pub fn is_alive(&self, block: usize) -> bool {
+        self.0[block / 64] >> (block % 64) & 1 == 1
+    }
+
+    fn set_alive(&mut self, block: usize) {
+        self.0[block / 64] |= 1 << (block % 64);
+    }
+
+    /// get the birth and survival bits if the next state only depends on the number of neighbours
+    pub fn get_counts(&self) -> Option<(u16, u16)> {
+        let (mut birth, mut survival) = (0u16, 0u16);
+        for block in (0..512).filter(|&block| self.is_alive(block)) {
+            match block & CELL != 0 {
+                true => survival |= 1 << count(block),
+                false => birth |= 1 << count(block),
+            }
+        }
+        match Table::from_counts(birth, survival) == *self {
+            true => Some((birth, survival)),
+            false => None,
+        }
+    }
+
+    /// returns true if the rotations and reflections of a block have the same next state
+    pub fn is_isotropic(&self) -> bool {
+        (0..512).all(|block| {
+            [Transform::Rotate90, Transform::FlipHorizontal]
+                .into_iter()
+                .all(|transform| {
+                    self.is_alive(transform_block(block, transform)) == self.is_alive(block)
+                })
+        })
+    }
+
+    /// write the birth and survival parts in Hensel notation, only for isotropic tables
+    /// the letters left out are written when they are fewer than the letters in
+    pub fn to_hensel(&self) -> (String, String) {
+        let write = |cell: usize| {
+            let mut part = String::new();
+            for n in 0..=8 {
+                let letters = get_letters(n);
+                let alive: Vec<char> = letters
+                    .iter()
+                    .cloned()
+                    .filter(|&letter| self.is_alive(get_representative(n, letter) | cell))
+                    .collect();
+                // counts without letters, 0 and 8, only have one block
+                let all = match letters.is_empty() {
+                    true => self.is_alive(get_representative(n, ' ') | cell),
+                    false => alive.len() == letters.len(),
+                };
+                if all {
+                    part.push_str(&n.to_string());
+                } else if !alive.is_empty() {
+                    part.push_str(&n.to_string());
+                    match letters.len() - alive.len() < alive.len() {
+                        true => {
+                            part.push('-');
+                            part.extend(letters.iter().filter(|letter| !alive.contains(letter)));
+                        }
+                        false => part.extend(alive),
+                    }
+                }
+            }
+            part
+        };
+        (write(0), write(CELL))
+    }
+
+    /// write the base64 digits of the MAP notation
+    pub fn to_map(&self) -> String {
+        let mut digits = String::new();
+        for start in (0..512).step_by(6) {
+            let value = (0..6)
+                .filter(|&bit| start + bit < 512 && self.is_alive(reverse(start + bit)))
+                .fold(0, |value, bit| value | 1 << (5 - bit));
+            digits.push(BASE64[value] as char);
+        }
+        digits
+    }
+}
+
+/// parse the counts and letters of a part of a Hensel rulestring
+/// returns the neighbours of every block in it
+fn parse_configurations(part: &str) -> Result<Vec<usize>, RuleError> {
+    let mut blocks = Vec::new();
+    let mut chars = part.chars().peekable();
+    while let Some(c) = chars.next() {
+        let n = c.to_digit(10).ok_or(RuleError::InvalidDigit(c))?;
+        if n > 8 {
+            return Err(RuleError::InvalidNeighbourCount(n));
+        }
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut letters = Vec::new();
+        while let Some(letter) = chars.next_if(|c| c.is_ascii_lowercase()) {
+            if !get_letters(n).contains(&letter) {
+                return Err(RuleError::InvalidLetter(n, letter));
+            }
+            letters.push(letter);
+        }
+        if negated && letters.is_empty() {
+            return Err(RuleError::InvalidDigit('-'));
+        }
+
+        for neighbours in (0..512).filter(|&block| block & CELL == 0 && count(block) == n) {
+            let included = letters.is_empty()
+                || letters
+                    .iter()
+                    .any(|&letter| is_letter(neighbours, n, letter))
+                    != negated;
+            if included {
+                blocks.push(neighbours);
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// get the letters of the configurations of n alive neighbours
+fn get_letters(n: u32) -> Vec<char> {
+    REPRESENTATIVES[n.min(8 - n) as usize]
+        .iter()
+        .map(|&(letter, _)| letter)
+        .collect()
+}
+
+/// get a block of n alive neighbours with the configuration of the letter
+/// the letter is ignored for 0 and 8 neighbours
+fn get_representative(n: u32, letter: char) -> usize {
+    let neighbours = |n: u32| {
+        REPRESENTATIVES[n as usize]
+            .iter()
+            .find(|&&(l, _)| l == letter)
+            .map_or(0, |&(_, block)| block)
+    };
+    match n {
+        0..=4 => neighbours(n),
+        _ => NEIGHBOURS ^ neighbours(8 - n),
+    }
+}
+
+/// returns true if the neighbours of a block are a rotation or reflection of the letter
+fn is_letter(neighbours: usize, n: u32, letter: char) -> bool {
+    let mut blocks = vec![get_representative(n, letter)];
+    let mut i = 0;
+    while i < blocks.len() {
+        for transform in [Transform::Rotate90, Transform::FlipHorizontal] {
+            let block = transform_block(blocks[i], transform);
+            if !blocks.contains(&block) {
+                blocks.push(block);
+            }
+        }
+        i += 1;
+    }
+    blocks.contains(&neighbours)
+}
+
+/// move the bits of a block with a rotation or reflection around the cell
+fn transform_block(block: usize, transform: Transform) -> usize {
+    (0..9)
+        .filter(|bit| block >> bit & 1 == 1)
+        .map(|bit| {
+            // rows go down from the top, the transforms have y growing upwards
+            let (x, y) = transform.apply((bit % 3 - 1, 1 - bit / 3));
+            1 << ((1 - y) * 3 + x + 1)
+        })
+        .fold(0, |block, bit| block | bit)
+}
+
+/// number of alive neighbours in a block
+fn count(block: usize) -> u32 {
+    (block & NEIGHBOURS).count_ones()
+}
+
+/// the MAP notation has the top left cell as the highest bit of the block
+fn reverse(block: usize) -> usize {
+    (block as u16).reverse_bits() as usize >> 7
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn letters_split_every_count() {
+        for n in 1..=7 {
+            let blocks: Vec<usize> = (0..512)
+                .filter(|&block| block & CELL == 0 && count(block) == n)
+                .collect();
+            for &block in &blocks {
+                let letters = get_letters(n)
+                    .into_iter()
+                    .filter(|&letter| is_letter(block, n, letter))
+                    .count();
+                assert_eq!(letters, 1, "block {} of {} neighbours", block, n);
+            }
+        }
+    }
+
+    #[test]
+    fn hensel_and_map_notations() {
+        let conway = Table::from_counts(1 << 3, (1 << 2) | (1 << 3));
+        assert_eq!(conway.get_counts(), Some((1 << 3, (1 << 2) | (1 << 3))));
+        assert_eq!(
+            conway.to_map(),
+            "ARYXfhZofugWaH7oaIDogBZofuhogOiAaIDogIAAgAAWaH7oaIDogGiA6ICAAIAAaIDogIAAgACAAIAAAAAAAA"
+        );
+        assert_eq!(Table::parse_map(&conway.to_map()), Ok(conway));
+        assert_eq!(Table::parse_hensel("3", "23"), Ok(conway));
+
+        let table = Table::parse_hensel("2-a3", "12ck").unwrap();
+        assert!(table.is_isotropic());
+        assert_eq!(table.get_counts(), None);
+        assert_eq!(table.to_hensel(), ("2-a3".to_string(), "12ck".to_string()));
+        assert_eq!(Table::parse_hensel("2cekin3", "12ck"), Ok(table));
+        assert_eq!(Table::parse_map(&table.to_map()), Ok(table));
+
+        // tlife and its MAP string
+        let tlife = Table::parse_hensel("3", "2-i34q").unwrap();
+        assert_eq!(
+            tlife.to_map(),
+            "ARYXfhZofmgWaH7oaIDqgBZoXuhogOiAaIDoiIAAgAAWaH74aIDogGiA6ICAAIAAaIDogIAAwACAAIAAAAAAAA"
+        );
+        assert_eq!(tlife.to_hensel(), ("3".to_string(), "2-i34q".to_string()));
+
+        assert_eq!(
+            Table::parse_hensel("2x", ""),
+            Err(RuleError::InvalidLetter(2, 'x'))
+        );
+        assert_eq!(
+            Table::parse_hensel("1k", ""),
+            Err(RuleError::InvalidLetter(1, 'k'))
+        );
+        assert_eq!(
+            Table::parse_hensel("2-", ""),
+            Err(RuleError::InvalidDigit('-'))
+        );
+    }
+}
@@ -0,0 +1,831 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use super::formats::ParseError;
+use super::rule::{MAX_STATES, Neighbourhood, Rule};
+
+/// Wireworld in the format of the Golly rule files
+pub const WIREWORLD: &str = "@RULE Wireworld
+Electrons move along the conductors: empty, electron head, electron tail and conductor
+
+@TABLE
+n_states:4
+neighborhood:Moore
+symmetries:permute
+
+var a={0,1,2,3}
+var b={0,1,2,3}
+var c={0,1,2,3}
+var d={0,1,2,3}
+var e={0,1,2,3}
+var f={0,1,2,3}
+var g={0,1,2,3}
+var h={0,1,2,3}
+var i={0,2,3}
+var j={0,2,3}
+var k={0,2,3}
+var l={0,2,3}
+var m={0,2,3}
+var n={0,2,3}
+var o={0,2,3}
+
+# heads become tails and tails become conductors
+1,a,b,c,d,e,f,g,h,2
+2,a,b,c,d,e,f,g,h,3
+# conductors with one or two heads around become heads
+3,1,i,j,k,l,m,n,o,1
+3,1,1,i,j,k,l,m,n,1
+
+@COLORS
+0 48 48 48
+1 0 128 255
+2 255 255 255
+3 255 128 0
+";
+
+/// the rule tables that can be named in rulestrings, Wireworld is always there
+/// the registry lives as long as the process and its tables are never removed,
+/// register only adds or replaces them, the front-ends do it once at startup
+/// for the file given with --rule-table before parsing any pattern,
+/// so every rulestring of a run sees the same tables
+/// rules own their table, so replacing a table doesn't change the rules already parsed
+static RULE_TABLES: LazyLock<RwLock<Vec<Arc<RuleTable>>>> = LazyLock::new(|| {
+    let wireworld = RuleTable::parse(WIREWORLD).expect("invalid Wireworld table");
+    RwLock::new(vec![Arc::new(wireworld)])
+});
+
+/// most inputs of a transition, the cell and the 8 neighbours of the Moore neighbourhood
+pub const MAX_INPUTS: usize = 9;
+
+/// most next states remembered by a cache before it is emptied
+const MAX_CACHED_STATES: usize = 1 << 20;
+
+/// a cell or neighbour of a transition
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Input {
+    /// any of the states
+    States(Vec<u8>),
+    /// a variable used more than once in the transition, all its inputs have the same state
+    /// the number is the place of the variable in the bound variables of the transition
+    Bound(usize),
+}
+
+/// the state a transition gives to the cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Output {
+    State(u8),
+    /// the state of a bound variable of the inputs
+    Bound(usize),
+}
+
+/// the cell and its neighbours before a change and the state of the cell after it
+/// bound are the states of the bound variables of the inputs
+/// permuted transitions match the neighbours in any order
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Transition {
+    inputs: Vec<Input>,
+    bound: Vec<Vec<u8>>,
+    output: Output,
+    permuted: bool,
+}
+
+impl Transition {
+    /// get the output of the transition if the states match its inputs
+    fn apply(&self, states: &[u8]) -> Option<u8> {
+        let mut bound = [None; MAX_INPUTS];
+        match self.permuted {
+            true => {
+                self.bind(&self.inputs[0], states[0], &mut bound)?;
+                // how many neighbours are in each state
+                let mut counts = [(0, 0); MAX_INPUTS];
+                let mut len = 0;
+                for &state in &states[1..] {
+                    match counts[..len].iter_mut().find(|(s, _)| *s == state) {
+                        Some((_, count)) => *count += 1,
+                        None => {
+                            counts[len] = (state, 1);
+                            len += 1;
+                        }
+                    }
+                }
+                if !self.assign(1, &mut counts[..len], &mut bound) {
+                    return None;
+                }
+            }
+            false => {
+                for (input, &state) in self.inputs.iter().zip(states) {
+                    self.bind(input, state, &mut bound)?;
+                }
+            }
+        }
+        match self.output {
+            Output::State(state) => Some(state),
+            Output::Bound(variable) => bound[variable],
+        }
+    }
+
+    /// check if a state matches an input, binding its variable the first time it is used
+    /// returns None if it doesn't match, or the variable bound by this call
+    fn bind(
+        &self,
+        input: &Input,
+        state: u8,
+        bound: &mut [Option<u8>; MAX_INPUTS],
+    ) -> Option<Option<usize>> {
+        match input {
+            Input::States(states) => states.contains(&state).then_some(None),
+            Input::Bound(variable) => match bound[*variable] {
+                Some(value) => (value == state).then_some(None),
+                None if !self.bound[*variable].contains(&state) => None,
+                None => {
+                    bound[*variable] = Some(state);
+                    Some(Some(*variable))
+                }
+            },
+        }
+    }
+
+    /// give a neighbour of the counts of each state to every input from the first one,
+    /// trying the states in turn until all the inputs match
+    fn assign(
+        &self,
+        first: usize,
+        counts: &mut [(u8, u8)],
+        bound: &mut [Option<u8>; MAX_INPUTS],
+    ) -> bool {
+        if first == self.inputs.len() {
+            return true;
+        }
+        for i in 0..counts.len() {
+            let (state, count) = counts[i];
+            if count == 0 {
+                continue;
+            }
+            let Some(variable) = self.bind(&self.inputs[first], state, bound) else {
+                continue;
+            };
+            counts[i].1 -= 1;
+            if self.assign(first + 1, counts, bound) {
+                return true;
+            }
+            counts[i].1 += 1;
+            if let Some(variable) = variable {
+                bound[variable] = None;
+            }
+        }
+        false
+    }
+}
+
+/// multi-state rule given by a table of transitions, as in the @TABLE section of Golly rule files
+/// the first transition matching a cell and its neighbours gives its next state,
+/// the cells matching none keep their state
+/// the neighbours are in the order of Golly, clockwise from the top
+/// by_state are the transitions that can match each state of the cell
+/// colors are the RGB colors of the states from the @COLORS section
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleTable {
+    name: String,
+    states: u8,
+    neighbourhood: Neighbourhood,
+    variables: Vec<Vec<u8>>,
+    transitions: Vec<Transition>,
+    by_state: Vec<Vec<usize>>,
+    colors: HashMap<u8, (u8, u8, u8)>,
+}
+
+/// tables are hashed by their name, tables with the same name are usually the same
+impl Hash for RuleTable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.states.hash(state);
+    }
+}
+
+impl RuleTable {
+    /// parse a Golly rule file with the @RULE, @TABLE and @COLORS sections
+    /// the other sections are ignored
+    ///
+    /// @RULE Name
+    /// @TABLE
+    /// n_states:3
+    /// neighborhood:vonNeumann
+    /// symmetries:rotate4
+    /// var a={0,1,2}
+    /// 0,1,a,0,0,2
+    /// @COLORS
+    /// 1 255 0 0
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut table = RuleTable {
+            name: String::new(),
+            states: 0,
+            neighbourhood: Neighbourhood::Moore,
+            variables: Vec::new(),
+            transitions: Vec::new(),
+            by_state: Vec::new(),
+            colors: HashMap::new(),
+        };
+        let mut names: HashMap<String, usize> = HashMap::new();
+        let mut symmetries = String::from("none");
+        let mut section = "";
+        // line of the @TABLE header
+        let mut table_line = None;
+
+        for (i, line) in input.lines().enumerate() {
+            let number = i + 1;
+            let error = |message: String| ParseError::new(number, message);
+            if let Some(header) = line.trim().strip_prefix('@') {
+                let (name, value) = header.split_once(' ').unwrap_or((header, ""));
+                section = name;
+                if name == "RULE" {
+                    table.name = value.trim().to_string();
+                }
+                if name == "TABLE" {
+                    table_line.get_or_insert(number);
+                }
+                continue;
+            }
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            match section {
+                "TABLE" => {
+                    if let Some((key, value)) = line.split_once(':') {
+                        let value = value.trim();
+                        match key.trim() {
+                            "n_states" => {
+                                table.states = value
+                                    .parse()
+                                    .ok()
+                                    .filter(|states| (2..=MAX_STATES).contains(states))
+                                    .ok_or_else(|| {
+                                        error(format!("invalid number of states '{}'", value))
+                                    })? as u8;
+                            }
+                            "neighborhood" | "neighbourhood" => {
+                                table.neighbourhood = match value {
+                                    "Moore" => Neighbourhood::Moore,
+                                    "vonNeumann" => Neighbourhood::VonNeumann,
+                                    "hexagonal" => Neighbourhood::Hexagonal,
+                                    _ => {
+                                        return Err(error(format!(
+                                            "unsupported neighbourhood '{}'",
+                                            value
+                                        )));
+                                    }
+                                }
+                            }
+                            "symmetries" => symmetries = value.to_string(),
+                            key => return Err(error(format!("unknown setting '{}'", key))),
+                        }
+                    } else if table.states == 0 {
+                        return Err(error(
+                            "n_states must come before the variables and transitions".to_string(),
+                        ));
+                    } else if let Some(variable) = line.strip_prefix("var ") {
+                        let (name, states) = variable.split_once('=').ok_or_else(|| {
+                            error(format!("invalid variable '{}'", variable.trim()))
+                        })?;
+                        let states = table.parse_set(states.trim(), &names).map_err(error)?;
+                        names.insert(name.trim().to_string(), table.variables.len());
+                        table.variables.push(states);
+                    } else {
+                        let permutations =
+                            get_permutations(table.neighbourhood, &symmetries).map_err(error)?;
+                        let transition = table.parse_transition(line, &names).map_err(error)?;
+                        table.add_transition(transition, &permutations);
+                    }
+                }
+                "COLORS" => {
+                    let values: Vec<u8> = line
+                        .split_whitespace()
+                        .map(|value| value.parse::<u8>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| error(format!("invalid color '{}'", line)))?;
+                    match values[..] {
+                        [state, r, g, b] => {
+                            table.colors.insert(state, (r, g, b));
+                        }
+                        // a gradient from the first to the last alive state
+                        [r1, g1, b1, r2, g2, b2] => {
+                            let last = table.states.saturating_sub(1).max(1);
+                            for state in 1..=last {
+                                let t = match last > 1 {
+                                    true => (state - 1) as f32 / (last - 1) as f32,
+                                    false => 0.0,
+                                };
+                                let mix =
+                                    |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+                                table
+                                    .colors
+                                    .insert(state, (mix(r1, r2), mix(g1, g2), mix(b1, b2)));
+                            }
+                        }
+                        _ => return Err(error(format!("invalid color '{}'", line))),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let Some(table_line) = table_line else {
+            return Err(ParseError::new(
+                input.lines().count(),
+                "missing @TABLE section",
+            ));
+        };
+        if table.states == 0 {
+            return Err(ParseError::new(table_line, "missing n_states"));
+        }
+        if table.name.is_empty() {
+            return Err(ParseError::new(1, "missing rule name"));
+        }
+        table.by_state = (0..table.states)
+            .map(|state| {
+                let transitions = table.transitions.iter().enumerate();
+                transitions
+                    .filter(|(_, transition)| match &transition.inputs[0] {
+                        Input::States(states) => states.contains(&state),
+                        Input::Bound(variable) => transition.bound[*variable].contains(&state),
+                    })
+                    .map(|(i, _)| i)
+                    .collect()
+            })
+            .collect();
+        Ok(table)
+    }
+
+    /// parse a set of states like "{0,1,a}", the variables are replaced by their states
+    /// a single state or variable doesn't need the braces
+    fn parse_set(&self, set: &str, names: &HashMap<String, usize>) -> Result<Vec<u8>, String> {
+        let items = set
+            .strip_prefix('{')
+            .and_then(|set| set.strip_suffix('}'))
+            .unwrap_or(set);
+        let mut states = Vec::new();
+        for item in items.split(',').map(str::trim) {
+            match (item.parse::<u8>(), names.get(item)) {
+                (Ok(state), _) => states.push(self.check_state(state)?),
+                (_, Some(&variable)) => states.extend(&self.variables[variable]),
+                _ => return Err(format!("unknown state or variable '{}'", item)),
+            }
+        }
+        states.sort_unstable();
+        states.dedup();
+        Ok(states)
+    }
+
+    fn check_state(&self, state: u8) -> Result<u8, String> {
+        match state < self.states {
+            true => Ok(state),
+            false => Err(format!("state {} out of range", state)),
+        }
+    }
+
+    /// parse a transition, the values are separated by commas
+    /// or written one after the other if they are all single digit states
+    fn parse_transition(
+        &self,
+        line: &str,
+        names: &HashMap<String, usize>,
+    ) -> Result<Transition, String> {
+        let values: Vec<String> = match line.contains(',') {
+            true => split_values(line),
+            false => line.chars().map(|c| c.to_string()).collect(),
+        };
+        let neighbours = self.neighbourhood.get_offsets(1).len();
+        if values.len() != neighbours + 2 {
+            return Err(format!(
+                "expected {} values in the transition '{}'",
+                neighbours + 2,
+                line
+            ));
+        }
+
+        // variables used more than once must take the same state in all their places
+        let mut uses: HashMap<&str, usize> = HashMap::new();
+        for value in &values {
+            *uses.entry(value.as_str()).or_insert(0) += 1;
+        }
+        let (output, inputs) = values.split_last().unwrap_or((&values[0], &[]));
+        // the variables bound in this transition, in the order of their first use
+        let mut variables: Vec<usize> = Vec::new();
+        let mut transition_inputs = Vec::new();
+        for value in inputs {
+            let input = match names.get(value.as_str()) {
+                Some(&variable) if uses[value.as_str()] > 1 => {
+                    let place = match variables.iter().position(|&v| v == variable) {
+                        Some(place) => place,
+                        None => {
+                            variables.push(variable);
+                            variables.len() - 1
+                        }
+                    };
+                    Input::Bound(place)
+                }
+                _ => Input::States(self.parse_set(value, names)?),
+            };
+            transition_inputs.push(input);
+        }
+        let output = match (output.parse::<u8>(), names.get(output.as_str())) {
+            (Ok(state), _) => Output::State(self.check_state(state)?),
+            (_, Some(variable)) => match variables.iter().position(|v| v == variable) {
+                Some(place) => Output::Bound(place),
+                None => return Err(format!("invalid output '{}'", output)),
+            },
+            _ => return Err(format!("invalid output '{}'", output)),
+        };
+        Ok(Transition {
+            inputs: transition_inputs,
+            bound: variables
+                .iter()
+                .map(|&variable| self.variables[variable].clone())
+                .collect(),
+            output,
+            permuted: false,
+        })
+    }
+
+    /// add a transition and its rotations and reflections given by the permutations of the neighbours
+    /// the transitions of every order are kept once, matching the neighbours in any order
+    fn add_transition(&mut self, transition: Transition, permutations: &Permutations) {
+        let list = match permutations {
+            Permutations::List(list) => list,
+            Permutations::All => {
+                self.transitions.push(Transition {
+                    permuted: true,
+                    ..transition
+                });
+                return;
+            }
+        };
+        let neighbours = &transition.inputs[1..];
+        let mut seen = HashSet::new();
+        for permutation in list {
+            let order: Vec<Input> = permutation.iter().map(|&i| neighbours[i].clone()).collect();
+            if !seen.insert(order.clone()) {
+                continue;
+            }
+            let mut inputs = vec![transition.inputs[0].clone()];
+            inputs.extend(order);
+            self.transitions.push(Transition {
+                inputs,
+                ..transition.clone()
+            });
+        }
+    }
+
+    /// get the next state of a cell from its state followed by the states of its neighbours
+    /// only the transitions that can match the state of the cell are tried
+    pub fn get_next_state(&self, states: &[u8]) -> u8 {
+        let Some(transitions) = self.by_state.get(states[0] as usize) else {
+            return states[0];
+        };
+        transitions
+            .iter()
+            .find_map(|&i| self.transitions[i].apply(states))
+            .unwrap_or(states[0])
+    }
+
+    /// returns true if every transition matches the neighbours in any order,
+    /// so the next state only depends on how many neighbours are in each state
+    fn is_permuted(&self) -> bool {
+        self.transitions
+            .iter()
+            .all(|transition| transition.permuted)
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_states(&self) -> u8 {
+        self.states
+    }
+
+    pub fn get_neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    /// get the positions of the neighbours in the order of the transitions,
+    /// clockwise from the top with y growing upwards
+    pub fn get_offsets(&self) -> Vec<(i32, i32)> {
+        match self.neighbourhood {
+            Neighbourhood::VonNeumann => vec![(0, 1), (1, 0), (0, -1), (-1, 0)],
+            Neighbourhood::Hexagonal => vec![(0, 1), (1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1)],
+            _ => vec![
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, -1),
+                (0, -1),
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+            ],
+        }
+    }
+
+    /// get the RGB colors of the states given in the @COLORS section
+    pub fn get_colors(&self) -> &HashMap<u8, (u8, u8, u8)> {
+        &self.colors
+    }
+}
+
+/// add a table to the tables that can be named in rulestrings,
+/// replacing a table with the same name, and get its rule
+/// the rules of a replaced table keep using it
+/// the table stays registered until the process ends
+pub fn register(table: RuleTable) -> Rule {
+    let mut tables = RULE_TABLES.write().expect("rule tables lock poisoned");
+    let table = Arc::new(table);
+    match tables
+        .iter_mut()
+        .find(|t| t.name.eq_ignore_ascii_case(&table.name))
+    {
+        Some(registered) => *registered = table.clone(),
+        None => tables.push(table.clone()),
+    }
+    Rule::from_rule_table(table)
+}
+
+/// read a Golly rule file and register its table so it can be named in rulestrings
+/// returns the rule of the table, errors start with the path of the file
+pub fn load(path: &str) -> Result<Rule, String> {
+    let input = fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+    let table = RuleTable::parse(&input).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(register(table))
+}
+
+/// get the rule of a registered table from its name, ignoring the case
+/// the result depends on the tables registered until now by the whole process
+pub fn find(name: &str) -> Option<Rule> {
+    let tables = RULE_TABLES.read().expect("rule tables lock poisoned");
+    tables
+        .iter()
+        .find(|table| table.name.eq_ignore_ascii_case(name))
+        .map(|table| Rule::from_rule_table(table.clone()))
+}
+
+/// next states of a rule table remembered between the steps of a universe
+/// the neighbours of tables that don't depend on their order are sorted,
+/// so every order of the same neighbours shares the same entry
+pub struct TableCache {
+    table: Arc<RuleTable>,
+    permuted: bool,
+    next_states: HashMap<[u8; MAX_INPUTS], u8>,
+}
+
+impl TableCache {
+    pub fn new(table: Arc<RuleTable>) -> Self {
+        TableCache {
+            permuted: table.is_permuted(),
+            table,
+            next_states: HashMap::new(),
+        }
+    }
+
+    /// get the next state of a cell from its state followed by the states of its neighbours
+    pub fn get_next_state(&mut self, states: &[u8]) -> u8 {
+        let mut key = [0; MAX_INPUTS];
+        key[..states.len()].copy_from_slice(states);
+        if self.permuted {
+            key[1..states.len()].sort_unstable();
+        }
+        if let Some(&state) = self.next_states.get(&key) {
+            return state;
+        }
+        if self.next_states.len() == MAX_CACHED_STATES {
+            self.next_states.clear();
+        }
+        let state = self.table.get_next_state(&key[..states.len()]);
+        self.next_states.insert(key, state);
+        state
+    }
+}
+
+/// orders of the neighbours given by the symmetries of a table
+enum Permutations {
+    List(Vec<Vec<usize>>),
+    /// every order, the transitions only depend on how many neighbours are in each state
+    All,
+}
+
+/// get the orders of the neighbours for the symmetries of a neighbourhood
+/// the neighbours go around the cell from the top, so rotations move them along the ring
+/// and reflections mirror it across the top neighbour
+fn get_permutations(
+    neighbourhood: Neighbourhood,
+    symmetries: &str,
+) -> Result<Permutations, String> {
+    let neighbours = neighbourhood.get_offsets(1).len();
+    // steps a rotation moves the neighbours and whether the reflections are included
+    let (step, reflect) = match (neighbourhood, symmetries) {
+        (_, "permute") => return Ok(Permutations::All),
+        (_, "none") => (neighbours, false),
+        (_, "reflect_horizontal") => (neighbours, true),
+        (Neighbourhood::Moore, "rotate4") => (2, false),
+        (Neighbourhood::Moore, "rotate4reflect") => (2, true),
+        (Neighbourhood::Moore, "rotate8") => (1, false),
+        (Neighbourhood::Moore, "rotate8reflect") => (1, true),
+        (Neighbourhood::VonNeumann, "rotate4") => (1, false),
+        (Neighbourhood::VonNeumann, "rotate4reflect") => (1, true),
+        (Neighbourhood::Hexagonal, "rotate2") => (3, false),
+        (Neighbourhood::Hexagonal, "rotate3") => (2, false),
+        (Neighbourhood::Hexagonal, "rotate6") => (1, false),
+        (Neighbourhood::Hexagonal, "rotate6reflect") => (1, true),
+        _ => return Err(format!("unsupported symmetries '{}'", symmetries)),
+    };
+    let mut permutations = Vec::new();
+    for rotation in (0..neighbours).step_by(step) {
+        let rotated: Vec<usize> = (0..neighbours)
+            .map(|i| (i + rotation) % neighbours)
+            .collect();
+        if reflect {
+            permutations.push(
+                (0..neighbours)
+                    .map(|i| rotated[(neighbours - i) % neighbours])
+                    .collect(),
+            );
+        }
+        permutations.push(rotated);
+    }
+    Ok(Permutations::List(permutations))
+}
+
+/// split the values of a transition on the commas that aren't in a set
+fn split_values(line: &str) -> Vec<String> {
+    let mut values = vec![String::new()];
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                values.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(value) = values.last_mut() {
+            value.push(c);
+        }
+    }
+    values
+        .iter()
+        .map(|value| value.trim().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::game_of_life::GameOfLife;
+    use super::*;
+
+    #[test]
+    fn wireworld_transitions() {
+        let table = RuleTable::parse(WIREWORLD).unwrap();
+        assert_eq!(table.get_name(), "Wireworld");
+        assert_eq!(table.get_states(), 4);
+        assert_eq!(table.get_colors().get(&1), Some(&(0, 128, 255)));
+        // head, tail and the conductor with one, two and three heads
+        assert_eq!(table.get_next_state(&[1, 0, 0, 3, 0, 0, 0, 0, 0]), 2);
+        assert_eq!(table.get_next_state(&[2, 1, 1, 1, 1, 0, 0, 0, 0]), 3);
+        assert_eq!(table.get_next_state(&[3, 0, 0, 0, 0, 0, 3, 1, 2]), 1);
+        assert_eq!(table.get_next_state(&[3, 1, 0, 0, 0, 0, 3, 1, 2]), 1);
+        assert_eq!(table.get_next_state(&[3, 1, 0, 1, 0, 0, 3, 1, 2]), 3);
+        assert_eq!(table.get_next_state(&[0, 1, 1, 0, 0, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn permuted_transitions() {
+        // every order of the neighbours is matched by the same transition
+        let wireworld = RuleTable::parse(WIREWORLD).unwrap();
+        assert_eq!(wireworld.transitions.len(), 4);
+
+        let input = "@RULE Pairs
+@TABLE
+n_states:3
+symmetries:permute
+var a={1,2}
+# a cell takes the state of exactly two neighbours in the same state
+0,a,a,0,0,0,0,0,0,a
+";
+        let table = Arc::new(RuleTable::parse(input).unwrap());
+        assert_eq!(table.get_next_state(&[0, 2, 0, 0, 0, 2, 0, 0, 0]), 2);
+        assert_eq!(table.get_next_state(&[0, 0, 0, 0, 0, 0, 0, 1, 1]), 1);
+        assert_eq!(table.get_next_state(&[0, 1, 0, 2, 0, 0, 0, 0, 0]), 0);
+        assert_eq!(table.get_next_state(&[0, 1, 1, 1, 0, 0, 0, 0, 0]), 0);
+
+        // the cache shares the next state of every order of the neighbours
+        let mut cache = TableCache::new(table);
+        assert_eq!(cache.get_next_state(&[0, 2, 0, 0, 0, 2, 0, 0, 0]), 2);
+        assert_eq!(cache.get_next_state(&[0, 0, 2, 2, 0, 0, 0, 0, 0]), 2);
+        assert_eq!(cache.get_next_state(&[0, 0, 1, 2, 0, 0, 0, 0, 0]), 0);
+        assert_eq!(cache.next_states.len(), 2);
+    }
+
+    #[test]
+    fn variables_and_symmetries() {
+        let input = "@RULE Test
+@TABLE
+n_states:3
+neighborhood:vonNeumann
+symmetries:rotate4
+var a={1,2}
+var b={0,a}
+# a cell becomes the state of two opposite neighbours in the same state
+0,a,b,a,0,a
+0,{1,2},0,0,0,2
+1,1,1,1,1,0
+@COLORS
+1 255 0 0
+";
+        let table = RuleTable::parse(input).unwrap();
+        // the bound a takes the same state in both places
+        assert_eq!(table.get_next_state(&[0, 2, 1, 2, 0]), 2);
+        assert_eq!(table.get_next_state(&[0, 1, 1, 2, 0]), 0);
+        // rotated by a quarter turn
+        assert_eq!(table.get_next_state(&[0, 0, 1, 2, 1]), 1);
+        assert_eq!(table.get_next_state(&[0, 0, 0, 0, 2]), 2);
+        assert_eq!(table.get_next_state(&[1, 1, 1, 1, 1]), 0);
+        assert_eq!(table.get_next_state(&[1, 1, 1, 1, 0]), 1);
+        assert_eq!(table.get_colors().get(&1), Some(&(255, 0, 0)));
+
+        let errors = [
+            ("@RULE Bad\n@TABLE\nn_states:2\n0,1,0,0,0,0,0,0,0,2\n", 4),
+            ("@RULE Bad\n@TABLE\nn_states:2\n0,1,0\n", 4),
+            (
+                "@RULE Bad\n@TABLE\nn_states:2\nsymmetries:rotate5\n0,1,0,0,0,0,0,0,0,1\n",
+                5,
+            ),
+            ("@RULE Bad\n@TABLE\nn_states:2\nvar a={0,x}\n", 4),
+            ("@RULE Bad\n@TABLE\nn_states:2\n0,1,0,0,0,0,0,0,0,a\n", 4),
+            ("@RULE Bad\n", 1),
+            ("@RULE Bad\n@TABLE\n", 2),
+            ("@RULE Bad\n\n@TABLE\nsymmetries:none\n", 3),
+            ("@RULE Bad\n@TABLE\nvar a={0,1}\nn_states:2\n", 3),
+            ("@RULE Bad\n@TABLE\n0,1,0,0,0,0,0,0,0,1\nn_states:2\n", 3),
+        ];
+        for (input, line) in errors {
+            assert_eq!(
+                RuleTable::parse(input).map_err(|e| e.line),
+                Err(line),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn reflections() {
+        // a cell is born with a neighbour on a side, never on a corner
+        let table = RuleTable::parse(
+            "@RULE Sides\n@TABLE\nn_states:2\nsymmetries:rotate4reflect\n0,1,0,0,0,0,0,0,0,1\n",
+        )
+        .unwrap();
+        assert_eq!(table.get_next_state(&[0, 0, 0, 0, 0, 0, 0, 1, 0]), 1);
+        assert_eq!(table.get_next_state(&[0, 0, 1, 0, 0, 0, 0, 0, 0]), 0);
+        assert_eq!(table.get_next_state(&[0, 0, 0, 0, 0, 0, 0, 0, 1]), 0);
+        let mut gol = GameOfLife::with_rule(Rule::from_rule_table(Arc::new(table)));
+        gol.set_cell_status(0, 0, true);
+        gol.step();
+        assert_eq!(gol.count_alive_cells(), 5);
+
+        // east and west are mirrored, north and south aren't
+        let table = RuleTable::parse(
+            "@RULE East\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:reflect_horizontal\n0,0,1,0,0,1\n",
+        )
+        .unwrap();
+        assert_eq!(table.get_next_state(&[0, 0, 1, 0, 0]), 1);
+        assert_eq!(table.get_next_state(&[0, 0, 0, 0, 1]), 1);
+        assert_eq!(table.get_next_state(&[0, 1, 0, 0, 0]), 0);
+        assert_eq!(table.get_next_state(&[0, 0, 0, 1, 0]), 0);
+    }
+
+    #[test]
+    fn registered_tables() {
+        let rule = find("WireWorld").unwrap();
+        assert_eq!(rule.get_states(), 4);
+        assert_eq!(rule.to_string(), "Wireworld");
+
+        // the registry is shared by every test, so the table has a name no other test uses
+        let table = RuleTable::parse("@RULE RegisteredTest\n@TABLE\nn_states:3\n").unwrap();
+        let rule = register(table);
+        assert_eq!(find("registeredtest"), Some(rule.clone()));
+        assert_eq!(Rule::parse("RegisteredTest"), Ok(rule.clone()));
+        assert!(rule.get_rule_table().is_some());
+
+        // a new table with the same name doesn't change the rules of the old one
+        let table = RuleTable::parse("@RULE RegisteredTest\n@TABLE\nn_states:5\n").unwrap();
+        let replaced = register(table);
+        assert_eq!(rule.get_states(), 3);
+        assert_eq!(replaced.get_states(), 5);
+        assert_ne!(rule, replaced);
+        assert_eq!(Rule::parse("RegisteredTest"), Ok(replaced));
+    }
+}
@@ -0,0 +1,243 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// fraction of alive cells used when no density is given
+pub const DEFAULT_DENSITY: f64 = 0.2;
+
+/// symmetries of the soups, named as in apgsearch
+/// C are rotations and D are rotations and reflections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    /// no symmetry
+    #[default]
+    C1,
+    /// half turn
+    C2,
+    /// quarter turn
+    C4,
+    /// reflection across the vertical axis
+    D2,
+    /// reflection across both axes
+    D4,
+    /// quarter turn and reflections across both axes and diagonals
+    D8,
+}
+
+impl Symmetry {
+    /// positions where a cell is copied, coords are doubled and relative to the center
+    /// so the center of areas with even sides is an integer
+    fn images(self, (u, v): (i64, i64)) -> Vec<(i64, i64)> {
+        match self {
+            Symmetry::C1 => vec![(u, v)],
+            Symmetry::C2 => vec![(u, v), (-u, -v)],
+            Symmetry::C4 => vec![(u, v), (-v, u), (-u, -v), (v, -u)],
+            Symmetry::D2 => vec![(u, v), (-u, v)],
+            Symmetry::D4 => vec![(u, v), (-u, v), (u, -v), (-u, -v)],
+            Symmetry::D8 => vec![
+                (u, v),
+                (-v, u),
+                (-u, -v),
+                (v, -u),
+                (-u, v),
+                (u, -v),
+                (v, u),
+                (-v, -u),
+            ],
+        }
+    }
+
+    /// returns true if the symmetry turns or reflects across a diagonal
+    /// so it needs a square area
+    fn needs_square(self) -> bool {
+        matches!(self, Symmetry::C4 | Symmetry::D8)
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_uppercase().as_str() {
+            "C1" => Ok(Symmetry::C1),
+            "C2" => Ok(Symmetry::C2),
+            "C4" => Ok(Symmetry::C4),
+            "D2" => Ok(Symmetry::D2),
+            "D4" => Ok(Symmetry::D4),
+            "D8" => Ok(Symmetry::D8),
+            _ => Err(format!("unknown symmetry '{}'", name)),
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// splitmix64 generator, kept in the crate so a seed gives the same soup in every version
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// uniform number in [0, 1) from the 53 high bits
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// a random pattern that can be recreated from its seed
+/// area is (min_x, min_y, max_x, max_y), density is the chance of a cell to be alive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Soup {
+    pub seed: u64,
+    pub density: f64,
+    pub area: (i32, i32, i32, i32),
+    pub symmetry: Symmetry,
+}
+
+impl Soup {
+    /// soup without symmetry and the default density
+    pub fn new(seed: u64, area: (i32, i32, i32, i32)) -> Self {
+        Soup {
+            seed,
+            density: DEFAULT_DENSITY,
+            area,
+            symmetry: Symmetry::C1,
+        }
+    }
+
+    /// soup with a seed taken from the system
+    pub fn random(area: (i32, i32, i32, i32)) -> Self {
+        Soup::new(rand::random(), area)
+    }
+
+    /// get the alive cells of the soup, the same seed always gives the same cells
+    /// with C4 and D8 the area grows to the smallest square containing it
+    pub fn get_cells(&self) -> Vec<(i32, i32)> {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = self.area;
+        if min_x > max_x {
+            std::mem::swap(&mut min_x, &mut max_x);
+        }
+        if min_y > max_y {
+            std::mem::swap(&mut min_y, &mut max_y);
+        }
+        if self.symmetry.needs_square() {
+            let side = (max_x - min_x).max(max_y - min_y);
+            max_x = min_x + side;
+            max_y = min_y + side;
+        }
+        let (sum_x, sum_y) = (min_x as i64 + max_x as i64, min_y as i64 + max_y as i64);
+
+        let mut rng = SplitMix64::new(self.seed);
+        let mut cells = Vec::new();
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let position = (2 * x as i64 - sum_x, 2 * y as i64 - sum_y);
+                let images = self.symmetry.images(position);
+                // each group of symmetric cells is decided once, by its smallest cell
+                if images.iter().min() != Some(&position) {
+                    continue;
+                }
+                if rng.next_f64() < self.density {
+                    let mut copies: Vec<(i32, i32)> = images
+                        .into_iter()
+                        .map(|(u, v)| (((u + sum_x) / 2) as i32, ((v + sum_y) / 2) as i32))
+                        .collect();
+                    copies.sort_unstable();
+                    copies.dedup();
+                    cells.extend(copies);
+                }
+            }
+        }
+        cells.sort_unstable();
+        cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn same_seed_same_cells() {
+        let soup = Soup::new(42, (0, 0, 15, 15));
+        assert_eq!(soup.get_cells(), soup.get_cells());
+        assert_ne!(soup.get_cells(), Soup::new(43, (0, 0, 15, 15)).get_cells());
+
+        let dense = Soup {
+            density: 1.0,
+            ..soup
+        };
+        assert_eq!(dense.get_cells().len(), 256);
+        let empty = Soup {
+            density: 0.0,
+            ..soup
+        };
+        assert!(empty.get_cells().is_empty());
+    }
+
+    #[test]
+    fn soups_never_change() {
+        // reference outputs of splitmix64
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+
+        let soup = Soup {
+            seed: 1,
+            density: 0.5,
+            area: (0, 0, 3, 3),
+            symmetry: Symmetry::C1,
+        };
+        assert_eq!(
+            soup.get_cells(),
+            vec![(0, 3), (1, 0), (2, 0), (2, 2), (3, 0), (3, 2), (3, 3)]
+        );
+    }
+
+    #[test]
+    fn symmetric_soups() {
+        for symmetry in [
+            Symmetry::C1,
+            Symmetry::C2,
+            Symmetry::C4,
+            Symmetry::D2,
+            Symmetry::D4,
+            Symmetry::D8,
+        ] {
+            let soup = Soup {
+                seed: 7,
+                density: 0.5,
+                area: (-3, 0, 4, 7),
+                symmetry,
+            };
+            let cells: HashSet<(i32, i32)> = soup.get_cells().into_iter().collect();
+            assert!(!cells.is_empty());
+            for &(x, y) in &cells {
+                // doubled coords relative to the center at (0.5, 3.5)
+                let (u, v) = (2 * x as i64 - 1, 2 * y as i64 - 7);
+                for (u, v) in symmetry.images((u, v)) {
+                    let image = (((u + 1) / 2) as i32, ((v + 7) / 2) as i32);
+                    assert!(cells.contains(&image), "{} {:?}", symmetry, image);
+                }
+            }
+        }
+        assert_eq!("d4".parse(), Ok(Symmetry::D4));
+        assert!("C3".parse::<Symmetry>().is_err());
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// number of previous generations compared with the current one
+const WINDOW: usize = 4096;
+
+/// long term behaviour of the universe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Stability {
+    /// no generation has repeated yet
+    #[default]
+    Evolving,
+    /// there are no alive cells
+    Extinct,
+    /// the cells don't change
+    Static,
+    /// the cells repeat after a number of generations
+    Periodic { period: u64 },
+    /// the cells repeat after a number of generations in another position
+    Translating { period: u64, dx: i32, dy: i32 },
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stability::Evolving => write!(f, "EVOLVING"),
+            Stability::Extinct => write!(f, "EXTINCT"),
+            Stability::Static => write!(f, "STATIC"),
+            Stability::Periodic { period } => write!(f, "PERIOD: {}", period),
+            Stability::Translating { period, dx, dy } => {
+                write!(f, "PERIOD: {} MOVING: ({},{})", period, dx, dy)
+            }
+        }
+    }
+}
+
+/// maximum number of cells kept to compare the generations
+/// the oldest generations are forgotten earlier when the patterns are big
+const MAX_KEPT_CELLS: usize = 1 << 22;
+
+/// a generation seen by the detector, with its cells moved so the origin is at (0, 0)
+/// and the state of each cell
+struct Generation {
+    step: u64,
+    origin: (i32, i32),
+    cells: Vec<((i32, i32), u8)>,
+}
+
+/// finds repeated generations comparing hashes of the cells and their states
+/// the hashes are relative to the bounding box so translated copies match,
+/// and the cells of generations with the same hash are compared to rule out collisions
+pub struct PeriodDetector {
+    seen: HashMap<u64, Vec<Generation>>,
+    order: VecDeque<u64>,
+    kept_cells: usize,
+    result: Stability,
+}
+
+impl PeriodDetector {
+    pub fn new() -> Self {
+        PeriodDetector {
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            kept_cells: 0,
+            result: Stability::Evolving,
+        }
+    }
+
+    /// forget the previous generations
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+        self.kept_cells = 0;
+        self.result = Stability::Evolving;
+    }
+
+    /// compare a generation with the previous ones and get the stability found so far
+    /// cells are the alive cells and states the cells in the other states than dead and alive,
+    /// such as the decaying cells of Generations rules
+    /// once a generation repeats the result doesn't change until the detector is cleared
+    pub fn observe(
+        &mut self,
+        cells: &HashSet<(i32, i32)>,
+        states: &HashMap<(i32, i32), u8>,
+        step: u64,
+    ) -> Stability {
+        if self.result != Stability::Evolving {
+            return self.result;
+        }
+
+        let Some(origin) = min_corner(cells.iter().chain(states.keys())) else {
+            self.result = Stability::Extinct;
+            return self.result;
+        };
+        let normalized = normalize(cells, states, origin);
+        let hash = normalized_hash(&normalized);
+
+        let previous = self
+            .seen
+            .get(&hash)
+            .and_then(|generations| generations.iter().find(|g| g.cells == normalized));
+        if let Some(previous) = previous {
+            let period = step.abs_diff(previous.step);
+            let dx = origin.0.wrapping_sub(previous.origin.0);
+            let dy = origin.1.wrapping_sub(previous.origin.1);
+            self.result = match (period, dx, dy) {
+                (0, _, _) => Stability::Evolving,
+                (1, 0, 0) => Stability::Static,
+                (period, 0, 0) => Stability::Periodic { period },
+                (period, dx, dy) => Stability::Translating { period, dx, dy },
+            };
+            return self.result;
+        }
+
+        while self.order.len() == WINDOW
+            || (!self.order.is_empty() && self.kept_cells + normalized.len() > MAX_KEPT_CELLS)
+        {
+            self.forget_oldest();
+        }
+        self.kept_cells += normalized.len();
+        self.seen.entry(hash).or_default().push(Generation {
+            step,
+            origin,
+            cells: normalized,
+        });
+        self.order.push_back(hash);
+        self.result
+    }
+
+    /// drop the first generation of the window
+    fn forget_oldest(&mut self) {
+        let Some(oldest) = self.order.pop_front() else {
+            return;
+        };
+        if let Some(generations) = self.seen.get_mut(&oldest) {
+            // generations with the same hash are in the order they were seen
+            let generation = generations.remove(0);
+            self.kept_cells -= generation.cells.len();
+            if generations.is_empty() {
+                self.seen.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get_stability(&self) -> Stability {
+        self.result
+    }
+}
+
+impl Default for PeriodDetector {
+    fn default() -> Self {
+        PeriodDetector::new()
+    }
+}
+
+/// minimum x and minimum y of the cells
+fn min_corner<'a>(cells: impl Iterator<Item = &'a (i32, i32)>) -> Option<(i32, i32)> {
+    cells
+        .cloned()
+        .reduce(|(min_x, min_y), (x, y)| (min_x.min(x), min_y.min(y)))
+}
+
+/// move the cells and their states so the origin is at (0, 0) and sort them
+fn normalize(
+    cells: &HashSet<(i32, i32)>,
+    states: &HashMap<(i32, i32), u8>,
+    origin: (i32, i32),
+) -> Vec<((i32, i32), u8)> {
+    let mut normalized: Vec<((i32, i32), u8)> = cells
+        .iter()
+        .map(|&cell| (cell, 1))
+        .chain(states.iter().map(|(&cell, &state)| (cell, state)))
+        // the cells can be further than i32::MAX from the origin, wrapping keeps them distinct
+        .map(|((x, y), state)| ((x.wrapping_sub(origin.0), y.wrapping_sub(origin.1)), state))
+        .collect();
+    normalized.sort_unstable();
+    normalized
+}
+
+/// hash of the cells already moved so the origin is at (0, 0)
+/// it doesn't depend on the order of the cells
+fn normalized_hash(cells: &[((i32, i32), u8)]) -> u64 {
+    cells
+        .iter()
+        .map(
+            |&((x, y), state)| mix(mix(((x as u32 as u64) << 32) | y as u32 as u64) ^ state as u64),
+        )
+        .fold(mix(cells.len() as u64), u64::wrapping_add)
+}
+
+/// splitmix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_translation() {
+        let mut detector = PeriodDetector::new();
+        let glider: HashSet<(i32, i32)> =
+            HashSet::from([(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]);
+        let moved: HashSet<(i32, i32)> = glider.iter().map(|&(x, y)| (x + 1, y - 1)).collect();
+        assert_eq!(
+            detector.observe(&glider, &HashMap::new(), 0),
+            Stability::Evolving
+        );
+        assert_eq!(
+            detector.observe(&moved, &HashMap::new(), 4),
+            Stability::Translating {
+                period: 4,
+                dx: 1,
+                dy: -1
+            }
+        );
+        detector.clear();
+        assert_eq!(detector.get_stability(), Stability::Evolving);
+        assert_eq!(
+            detector.observe(&HashSet::new(), &HashMap::new(), 0),
+            Stability::Extinct
+        );
+    }
+
+    #[test]
+    fn hash_collisions_are_not_repetitions() {
+        let mut detector = PeriodDetector::new();
+        let blinker = HashSet::from([(-1, 0), (0, 0), (1, 0)]);
+        let block = HashSet::from([(0, 0), (0, 1), (1, 0), (1, 1)]);
+        detector.observe(&blinker, &HashMap::new(), 0);
+
+        // make the blinker look like it had the hash of the block
+        let hash = |cells: &HashSet<(i32, i32)>| {
+            let origin = min_corner(cells.iter()).unwrap();
+            normalized_hash(&normalize(cells, &HashMap::new(), origin))
+        };
+        let generations = detector.seen.remove(&hash(&blinker)).unwrap();
+        detector.seen.insert(hash(&block), generations);
+        detector.order[0] = hash(&block);
+
+        assert_eq!(
+            detector.observe(&block, &HashMap::new(), 1),
+            Stability::Evolving
+        );
+        assert_eq!(detector.seen[&hash(&block)].len(), 2);
+        assert_eq!(
+            detector.observe(&block, &HashMap::new(), 2),
+            Stability::Static
+        );
+    }
+
+    #[test]
+    fn states_are_compared() {
+        // the same alive cells with a decaying cell that moves
+        let mut detector = PeriodDetector::new();
+        let cells = HashSet::from([(0, 0)]);
+        let states = |x| HashMap::from([((x, 1), 2)]);
+        assert_eq!(detector.observe(&cells, &states(0), 0), Stability::Evolving);
+        assert_eq!(detector.observe(&cells, &states(1), 1), Stability::Evolving);
+        assert_eq!(
+            detector.observe(&cells, &states(0), 2),
+            Stability::Periodic { period: 2 }
+        );
+
+        // cells at opposite ends of the plane
+        detector.clear();
+        let far = HashSet::from([(i32::MIN, i32::MIN), (i32::MAX, i32::MAX)]);
+        detector.observe(&far, &HashMap::new(), 0);
+        assert_eq!(
+            detector.observe(&far, &HashMap::new(), 1),
+            Stability::Static
+        );
+
+        // cells in other states aren't extinct
+        detector.clear();
+        assert_eq!(
+            detector.observe(&HashSet::new(), &states(0), 0),
+            Stability::Evolving
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Stability::Periodic { period: 2 }.to_string(), "PERIOD: 2");
+        assert_eq!(
+            Stability::Translating {
+                period: 4,
+                dx: 1,
+                dy: -1
+            }
+            .to_string(),
+            "PERIOD: 4 MOVING: (1,-1)"
+        );
+    }
+}
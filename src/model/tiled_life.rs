@@ -0,0 +1,385 @@
+use std::collections::{HashMap, HashSet};
+
+use super::game_of_life::GameOfLife;
+use super::rule::Rule;
+
+/// side of the tiles, a row of a tile fits in a u64
+const TILE_SIZE: i32 = 64;
+
+/// rows of a 64x64 square of cells, from south to north
+/// bit x of a row is the cell in column x, from west to east
+type Tile = [u64; TILE_SIZE as usize];
+
+const EMPTY_TILE: Tile = [0; TILE_SIZE as usize];
+
+/// engine for dense universes
+/// the alive cells are stored in bitboard tiles indexed by their position
+/// the neighbours of 64 cells are counted at once with bitwise operations
+/// tiles that didn't change and whose neighbours didn't change are not computed again
+/// the universe is always the infinite plane
+pub struct TiledLife {
+    tiles: HashMap<(i32, i32), Tile>,
+    changed: HashSet<(i32, i32)>,
+    step: u64,
+    rule: Rule,
+}
+
+impl TiledLife {
+    pub fn new() -> Self {
+        TiledLife {
+            tiles: HashMap::new(),
+            changed: HashSet::new(),
+            step: 0,
+            rule: Rule::conway(),
+        }
+    }
+
+    /// fails if the rule isn't Life-like
+    pub fn with_rule(rule: Rule) -> Result<Self, String> {
+        let mut tiled = TiledLife::new();
+        tiled.set_rule(rule)?;
+        Ok(tiled)
+    }
+
+    /// change the rule used for the next steps
+    /// every tile is computed again on the next step
+    /// fails without changing the rule if it isn't Life-like,
+    /// since the bitwise sums only count the 8 neighbours of cells with 2 states
+    pub fn set_rule(&mut self, rule: Rule) -> Result<(), String> {
+        if !rule.is_life_like() {
+            return Err(format!(
+                "the tiled engine only simulates Life-like rules, not {}",
+                rule
+            ));
+        }
+        self.rule = rule;
+        self.changed.extend(self.tiles.keys().cloned());
+        Ok(())
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule.clone()
+    }
+
+    /// change the cell status from dead to alive or viceversa
+    /// in a given position
+    pub fn change_cell_status(&mut self, pos_x: i32, pos_y: i32) {
+        let alive = self.get_cell_status(pos_x, pos_y);
+        self.set_cell_status(pos_x, pos_y, !alive);
+    }
+
+    /// make the cell in a given position alive or dead
+    pub fn set_cell_status(&mut self, pos_x: i32, pos_y: i32, alive: bool) {
+        let (key, x, y) = split(pos_x, pos_y);
+        let bit = 1u64 << x;
+        self.changed.insert(key);
+        match alive {
+            true => self.tiles.entry(key).or_insert(EMPTY_TILE)[y] |= bit,
+            false => {
+                if let Some(tile) = self.tiles.get_mut(&key) {
+                    tile[y] &= !bit;
+                    if *tile == EMPTY_TILE {
+                        self.tiles.remove(&key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// returns true if the cell in a given position is alive
+    pub fn get_cell_status(&self, pos_x: i32, pos_y: i32) -> bool {
+        let (key, x, y) = split(pos_x, pos_y);
+        self.tiles
+            .get(&key)
+            .is_some_and(|tile| tile[y] >> x & 1 == 1)
+    }
+
+    /// do a step in the simulation
+    /// only the tiles next to a tile that changed in the last step are computed
+    pub fn step(&mut self) {
+        self.step += 1;
+
+        // tiles that can change, the empty ones next to a changed tile included
+        let mut candidates: HashSet<(i32, i32)> = HashSet::new();
+        for &(tx, ty) in &self.changed {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    candidates.insert((tx + dx, ty + dy));
+                }
+            }
+        }
+
+        let mut tiles = HashMap::with_capacity(self.tiles.len());
+        let mut changed = HashSet::new();
+        for (&key, &tile) in &self.tiles {
+            if !candidates.contains(&key) {
+                tiles.insert(key, tile);
+            }
+        }
+        for key in candidates {
+            let old = self.tiles.get(&key).unwrap_or(&EMPTY_TILE);
+            let new = self.next_tile(key);
+            if new != *old {
+                changed.insert(key);
+            }
+            if new != EMPTY_TILE {
+                tiles.insert(key, new);
+            }
+        }
+
+        self.tiles = tiles;
+        self.changed = changed;
+    }
+
+    pub fn count_alive_cells(&self) -> usize {
+        self.tiles
+            .values()
+            .flat_map(|tile| tile.iter())
+            .map(|row| row.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn get_steps_count(&self) -> u64 {
+        self.step
+    }
+
+    /// get the smallest area (min_x, min_y, max_x, max_y) containing every alive cell
+    /// returns None if there are no alive cells
+    pub fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)> {
+        self.tiles
+            .iter()
+            .map(|(&(tx, ty), tile)| {
+                let columns = tile.iter().fold(0, |columns, row| columns | row);
+                let min_y = tile.iter().position(|&row| row != 0).unwrap_or(0) as i32;
+                let max_y = tile.iter().rposition(|&row| row != 0).unwrap_or(0) as i32;
+                let min_x = columns.trailing_zeros() as i32;
+                let max_x = (TILE_SIZE - 1) - columns.leading_zeros() as i32;
+                let (origin_x, origin_y) = (tx * TILE_SIZE, ty * TILE_SIZE);
+                (
+                    origin_x + min_x,
+                    origin_y + min_y,
+                    origin_x + max_x,
+                    origin_y + max_y,
+                )
+            })
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)))
+    }
+
+    /// get a new hashset with the alive cell in a certain area
+    pub fn data_as_vec(&self, area: (i32, i32, i32, i32)) -> HashSet<(i32, i32)> {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = area;
+        if min_x > max_x {
+            std::mem::swap(&mut min_x, &mut max_x);
+        }
+        if min_y > max_y {
+            std::mem::swap(&mut min_y, &mut max_y);
+        }
+
+        let mut cells = HashSet::new();
+        for (&(tx, ty), tile) in &self.tiles {
+            let (origin_x, origin_y) = (tx as i64 * TILE_SIZE as i64, ty as i64 * TILE_SIZE as i64);
+            if origin_x > max_x as i64
+                || origin_y > max_y as i64
+                || origin_x + (TILE_SIZE as i64) <= min_x as i64
+                || origin_y + (TILE_SIZE as i64) <= min_y as i64
+            {
+                continue;
+            }
+            for (y, &row) in tile.iter().enumerate() {
+                let mut bits = row;
+                while bits != 0 {
+                    let x = bits.trailing_zeros();
+                    bits &= bits - 1;
+                    let (x, y) = ((origin_x + x as i64) as i32, (origin_y + y as i64) as i32);
+                    if x >= min_x && x <= max_x && y >= min_y && y <= max_y {
+                        cells.insert((x, y));
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    /// delete all alive cells and restart the step counter
+    pub fn clear_cells(&mut self) {
+        self.tiles.clear();
+        self.changed.clear();
+        self.step = 0;
+    }
+
+    /// compute the next generation of a tile
+    fn next_tile(&self, (tx, ty): (i32, i32)) -> Tile {
+        // the tile and its neighbours, [row][column] from south west to north east
+        let around = [-1, 0, 1].map(|dy| [-1, 0, 1].map(|dx| self.tiles.get(&(tx + dx, ty + dy))));
+        let row = |column: usize, y: i32| {
+            let (tile_row, y) = match y {
+                -1 => (0, TILE_SIZE as usize - 1),
+                TILE_SIZE => (2, 0),
+                y => (1, y as usize),
+            };
+            around[tile_row][column].map_or(0, |tile| tile[y])
+        };
+        // the cells of a row and the cells to their west and to their east
+        let shifted = |y: i32| {
+            let center = row(1, y);
+            let west = center << 1 | row(0, y) >> (TILE_SIZE - 1);
+            let east = center >> 1 | row(2, y) << (TILE_SIZE - 1);
+            (west, center, east)
+        };
+
+        let mut next = EMPTY_TILE;
+        let (mut below, mut current) = (shifted(-1), shifted(0));
+        for (y, next_row) in next.iter_mut().enumerate() {
+            let above = shifted(y as i32 + 1);
+            let mut count = [0u64; 4];
+            for neighbours in [
+                below.0, below.1, below.2, current.0, current.2, above.0, above.1, above.2,
+            ] {
+                add(&mut count, neighbours);
+            }
+
+            let alive = current.1;
+            for n in 0..=8 {
+                let with_n = equals(&count, n);
+                if self.rule.is_born(n) {
+                    *next_row |= with_n & !alive;
+                }
+                if self.rule.survives(n) {
+                    *next_row |= with_n & alive;
+                }
+            }
+            (below, current) = (current, above);
+        }
+        next
+    }
+}
+
+impl Default for TiledLife {
+    fn default() -> Self {
+        TiledLife::new()
+    }
+}
+
+impl TryFrom<&GameOfLife> for TiledLife {
+    type Error = String;
+
+    /// fails if the rule of the universe isn't Life-like
+    fn try_from(gol: &GameOfLife) -> Result<Self, Self::Error> {
+        let mut tiled = TiledLife::with_rule(gol.get_rule())?;
+        for &(x, y) in gol.get_alive_cells() {
+            tiled.set_cell_status(x, y, true);
+        }
+        tiled.step = gol.get_steps_count();
+        Ok(tiled)
+    }
+}
+
+impl From<&TiledLife> for GameOfLife {
+    fn from(tiled: &TiledLife) -> Self {
+        let mut gol = GameOfLife::with_rule(tiled.get_rule());
+        for (x, y) in tiled.data_as_vec((i32::MIN, i32::MIN, i32::MAX, i32::MAX)) {
+            gol.set_cell_status(x, y, true);
+        }
+        gol.set_steps_count(tiled.get_steps_count());
+        gol
+    }
+}
+
+/// get the tile of a position and the column and row inside of it
+fn split(pos_x: i32, pos_y: i32) -> ((i32, i32), u32, usize) {
+    (
+        (pos_x.div_euclid(TILE_SIZE), pos_y.div_euclid(TILE_SIZE)),
+        pos_x.rem_euclid(TILE_SIZE) as u32,
+        pos_y.rem_euclid(TILE_SIZE) as usize,
+    )
+}
+
+/// add one to the counters of the set bits
+/// each counter is stored in the same bit of the 4 words, the lowest bit first
+fn add(count: &mut [u64; 4], mut carry: u64) {
+    for bit in count.iter_mut() {
+        let next = *bit & carry;
+        *bit ^= carry;
+        carry = next;
+    }
+}
+
+/// bits whose counter is equal to n
+fn equals(count: &[u64; 4], n: u32) -> u64 {
+    count
+        .iter()
+        .enumerate()
+        .fold(u64::MAX, |mask, (i, &bit)| match n >> i & 1 {
+            1 => mask & bit,
+            _ => mask & !bit,
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_and_get_cells() {
+        let mut tiled = TiledLife::new();
+        tiled.change_cell_status(0, 0);
+        tiled.change_cell_status(-1, -65);
+        tiled.change_cell_status(63, 64);
+        assert!(tiled.get_cell_status(-1, -65));
+        assert_eq!(tiled.count_alive_cells(), 3);
+        tiled.change_cell_status(0, 0);
+        assert!(!tiled.get_cell_status(0, 0));
+        assert_eq!(
+            tiled.data_as_vec((-10, -100, 100, 100)),
+            HashSet::from([(-1, -65), (63, 64)])
+        );
+    }
+
+    #[test]
+    fn matches_game_of_life() {
+        let mut gol = GameOfLife::with_rule(Rule::parse("B36/S23").unwrap());
+        gol.randomize_area(-70, -70, 70, 70);
+        let mut tiled = TiledLife::try_from(&gol).unwrap();
+
+        for _ in 0..30 {
+            gol.step();
+            tiled.step();
+            assert_eq!(
+                gol.get_alive_cells(),
+                &tiled.data_as_vec((-200, -200, 200, 200))
+            );
+        }
+        let back = GameOfLife::from(&tiled);
+        assert_eq!(back.get_alive_cells(), gol.get_alive_cells());
+        assert_eq!(back.get_steps_count(), 30);
+
+        assert!(tiled.set_rule(Rule::parse("/2/3").unwrap()).is_err());
+        assert_eq!(tiled.get_rule(), gol.get_rule());
+    }
+
+    #[test]
+    fn stable_tiles_are_skipped() {
+        let mut tiled = TiledLife::new();
+        for (x, y) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+            tiled.set_cell_status(x, y, true);
+        }
+        tiled.step();
+        assert!(tiled.changed.is_empty());
+        tiled.step();
+        assert_eq!(tiled.count_alive_cells(), 4);
+
+        // a glider crossing the edges between tiles
+        tiled.clear_cells();
+        for (x, y) in [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)] {
+            tiled.set_cell_status(x + 60, y + 2, true);
+        }
+        for _ in 0..40 {
+            tiled.step();
+        }
+        let expected: HashSet<(i32, i32)> = [(1, 0), (2, -1), (0, -2), (1, -2), (2, -2)]
+            .into_iter()
+            .map(|(x, y)| (x + 70, y - 8))
+            .collect();
+        assert_eq!(tiled.data_as_vec((0, -100, 100, 100)), expected);
+    }
+}
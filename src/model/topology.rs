@@ -0,0 +1,228 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// largest width or height of a board, so that its coords fit in an i32
+pub const MAX_SIZE: u32 = i32::MAX as u32;
+
+/// shape of the universe
+/// bounded topologies have a board of width x height cells
+/// going from (-width / 2, -height / 2) to (width - 1 - width / 2, height - 1 - height / 2)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Topology {
+    /// unbounded plane
+    #[default]
+    Infinite,
+    /// the cells outside the board are always dead
+    Plane { width: u32, height: u32 },
+    /// opposite edges are joined
+    Torus { width: u32, height: u32 },
+    /// left and right edges are joined, top and bottom edges are joined with a twist
+    KleinBottle { width: u32, height: u32 },
+    /// both pairs of opposite edges are joined with a twist
+    CrossSurface { width: u32, height: u32 },
+}
+
+impl Topology {
+    /// parse a topology in the notation used after the ':' of a rulestring
+    /// "P30,20" plane, "T30,20" torus, "K30,20" Klein bottle, "C30,20" cross-surface
+    /// a single size like "T30" is used for both width and height
+    /// the sizes go from 1 to MAX_SIZE
+    pub fn parse(topology: &str) -> Result<Self, TopologyError> {
+        let topology = topology.trim();
+        let invalid = || TopologyError::InvalidFormat(topology.to_string());
+
+        let mut chars = topology.chars();
+        let kind = chars.next().ok_or_else(invalid)?.to_ascii_uppercase();
+        let sizes: Vec<u32> = chars
+            .as_str()
+            .split(',')
+            .map(|size| size.trim().parse::<u32>().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        let (width, height) = match sizes[..] {
+            [size] => (size, size),
+            [width, height] => (width, height),
+            _ => return Err(invalid()),
+        };
+        if let Some(&size) = [width, height]
+            .iter()
+            .find(|&&size| size == 0 || size > MAX_SIZE)
+        {
+            return Err(TopologyError::InvalidSize(size));
+        }
+
+        match kind {
+            'P' => Ok(Topology::Plane { width, height }),
+            'T' => Ok(Topology::Torus { width, height }),
+            'K' => Ok(Topology::KleinBottle { width, height }),
+            'C' => Ok(Topology::CrossSurface { width, height }),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// get the area (min_x, min_y, max_x, max_y) of the board
+    /// returns None for the infinite plane
+    pub fn get_bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        let (width, height) = match *self {
+            Topology::Infinite => return None,
+            Topology::Plane { width, height }
+            | Topology::Torus { width, height }
+            | Topology::KleinBottle { width, height }
+            | Topology::CrossSurface { width, height } => (width as i32, height as i32),
+        };
+        let (min_x, min_y) = (-width / 2, -height / 2);
+        Some((min_x, min_y, min_x + width - 1, min_y + height - 1))
+    }
+
+    /// returns true if the position is inside the board
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        match self.get_bounds() {
+            Some((min_x, min_y, max_x, max_y)) => {
+                x >= min_x && x <= max_x && y >= min_y && y <= max_y
+            }
+            None => true,
+        }
+    }
+
+    /// move a position outside the board to the cell it is joined with
+    /// returns None if the position is outside a plane board
+    pub fn wrap(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let Some((min_x, min_y, max_x, max_y)) = self.get_bounds() else {
+            return Some((x, y));
+        };
+        let (twist_x, twist_y) = match self {
+            Topology::Plane { .. } => {
+                return self.contains(x, y).then_some((x, y));
+            }
+            Topology::KleinBottle { .. } => (false, true),
+            Topology::CrossSurface { .. } => (true, true),
+            _ => (false, false),
+        };
+
+        let (mut x, mut y) = (x, y);
+        if y < min_y || y > max_y {
+            y = min_y + (y - min_y).rem_euclid(max_y - min_y + 1);
+            if twist_y {
+                x = min_x + max_x - x;
+            }
+        }
+        if x < min_x || x > max_x {
+            x = min_x + (x - min_x).rem_euclid(max_x - min_x + 1);
+            if twist_x {
+                y = min_y + max_y - y;
+            }
+        }
+        Some((x, y))
+    }
+}
+
+impl FromStr for Topology {
+    type Err = TopologyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Topology::parse(s)
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (kind, width, height) = match *self {
+            Topology::Infinite => return Ok(()),
+            Topology::Plane { width, height } => ('P', width, height),
+            Topology::Torus { width, height } => ('T', width, height),
+            Topology::KleinBottle { width, height } => ('K', width, height),
+            Topology::CrossSurface { width, height } => ('C', width, height),
+        };
+        write!(f, "{}{},{}", kind, width, height)
+    }
+}
+
+/// errors found while parsing a topology
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyError {
+    InvalidFormat(String),
+    /// the width or height of the board is 0 or larger than MAX_SIZE
+    InvalidSize(u32),
+}
+
+impl fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TopologyError::InvalidFormat(s) => write!(f, "invalid topology '{}'", s),
+            TopologyError::InvalidSize(n) => {
+                write!(f, "board size {} out of range 1-{}", n, MAX_SIZE)
+            }
+        }
+    }
+}
+
+impl Error for TopologyError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_topologies() {
+        assert_eq!(
+            Topology::parse("T30,20").unwrap(),
+            Topology::Torus {
+                width: 30,
+                height: 20
+            }
+        );
+        assert_eq!(
+            Topology::parse("p10").unwrap(),
+            Topology::Plane {
+                width: 10,
+                height: 10
+            }
+        );
+        assert_eq!(Topology::parse("K8,6").unwrap().to_string(), "K8,6");
+        assert_eq!(Topology::parse("T0,10"), Err(TopologyError::InvalidSize(0)));
+        assert_eq!(
+            Topology::parse("T2147483648"),
+            Err(TopologyError::InvalidSize(2147483648))
+        );
+        assert_eq!(
+            "P10,3000000000".parse::<Topology>(),
+            Err(TopologyError::InvalidSize(3000000000))
+        );
+        let largest = Topology::parse("T2147483647").unwrap();
+        assert_eq!(
+            largest.get_bounds(),
+            Some((-1073741823, -1073741823, 1073741823, 1073741823))
+        );
+        assert!(Topology::parse("X10,10").is_err());
+        assert!(Topology::parse("T10,10,10").is_err());
+        assert!(Topology::parse("").is_err());
+    }
+
+    #[test]
+    fn bounds() {
+        assert_eq!(Topology::Infinite.get_bounds(), None);
+        assert_eq!(
+            Topology::parse("T4,5").unwrap().get_bounds(),
+            Some((-2, -2, 1, 2))
+        );
+    }
+
+    #[test]
+    fn wrap_positions() {
+        let plane = Topology::parse("P4,4").unwrap();
+        assert_eq!(plane.wrap(1, 1), Some((1, 1)));
+        assert_eq!(plane.wrap(2, 1), None);
+
+        let torus = Topology::parse("T4,4").unwrap();
+        assert_eq!(torus.wrap(2, 1), Some((-2, 1)));
+        assert_eq!(torus.wrap(-3, -3), Some((1, 1)));
+
+        let klein = Topology::parse("K4,4").unwrap();
+        assert_eq!(klein.wrap(2, 1), Some((-2, 1)));
+        assert_eq!(klein.wrap(0, 2), Some((-1, -2)));
+
+        let cross = Topology::parse("C4,4").unwrap();
+        assert_eq!(cross.wrap(2, 0), Some((-2, -1)));
+        assert_eq!(cross.wrap(0, -3), Some((-1, 1)));
+    }
+}
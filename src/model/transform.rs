@@ -0,0 +1,198 @@
+/// rotations, reflections and translations of cells
+/// rotations are clockwise as seen on the screen, where y grows upwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// mirror left and right
+    FlipHorizontal,
+    /// mirror top and bottom
+    FlipVertical,
+    /// mirror across the diagonal from the top left to the bottom right
+    FlipDiagonal,
+    /// move the cells by (dx, dy)
+    Translate(i32, i32),
+}
+
+impl Transform {
+    /// move a cell, rotations and reflections keep the origin in place
+    pub fn apply(self, (x, y): (i32, i32)) -> (i32, i32) {
+        match self {
+            Transform::Rotate90 => (y, -x),
+            Transform::Rotate180 => (-x, -y),
+            Transform::Rotate270 => (-y, x),
+            Transform::FlipHorizontal => (-x, y),
+            Transform::FlipVertical => (x, -y),
+            Transform::FlipDiagonal => (-y, -x),
+            Transform::Translate(dx, dy) => (x + dx, y + dy),
+        }
+    }
+
+    /// the transform that undoes this one
+    pub fn inverse(self) -> Transform {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            Transform::Translate(dx, dy) => Transform::Translate(-dx, -dy),
+            transform => transform,
+        }
+    }
+
+    /// returns true if the transform keeps the neighbours of the hexagonal neighbourhood,
+    /// where (-1, 1) and (1, -1) are neighbours of the origin but (1, 1) and (-1, -1) aren't
+    /// the other rotations and reflections change how the cells evolve
+    pub fn is_hexagonal(self) -> bool {
+        matches!(
+            self,
+            Transform::Rotate180 | Transform::FlipDiagonal | Transform::Translate(..)
+        )
+    }
+
+    /// get the area (min_x, min_y, max_x, max_y) taken by an area after the transform
+    /// rotated and reflected areas keep their center, rounded so turning back gives the same area
+    pub fn apply_to_area(self, area: (i32, i32, i32, i32)) -> (i32, i32, i32, i32) {
+        let (min_x, min_y, max_x, max_y) = area;
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        match self {
+            Transform::Rotate90 | Transform::Rotate270 | Transform::FlipDiagonal => {
+                let min_x = min_x + (width - height) / 2;
+                let min_y = min_y + (height - width) / 2;
+                (min_x, min_y, min_x + height, min_y + width)
+            }
+            Transform::Translate(dx, dy) => (min_x + dx, min_y + dy, max_x + dx, max_y + dy),
+            _ => area,
+        }
+    }
+}
+
+/// get the smallest area (min_x, min_y, max_x, max_y) containing every cell
+/// returns None if there are no cells
+pub fn bounding_box<'a>(
+    cells: impl IntoIterator<Item = &'a (i32, i32)>,
+) -> Option<(i32, i32, i32, i32)> {
+    let mut cells = cells.into_iter();
+    let &(x, y) = cells.next()?;
+    Some(
+        cells.fold((x, y, x, y), |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        }),
+    )
+}
+
+/// transform the cells of an area so they end in the area returned by apply_to_area
+pub fn transform_cells(
+    cells: &[(i32, i32)],
+    area: (i32, i32, i32, i32),
+    transform: Transform,
+) -> Vec<(i32, i32)> {
+    let new_area = transform.apply_to_area(area);
+
+    // the corners of the area after the transform give the offset to the new area
+    let (min_x, min_y, max_x, max_y) = area;
+    let (corner_x, corner_y) = [(min_x, min_y), (max_x, max_y)]
+        .into_iter()
+        .map(|corner| transform.apply(corner))
+        .fold((i32::MAX, i32::MAX), |(min_x, min_y), (x, y)| {
+            (min_x.min(x), min_y.min(y))
+        });
+    let offset = (new_area.0 - corner_x, new_area.1 - corner_y);
+
+    cells
+        .iter()
+        .map(|&cell| {
+            let (x, y) = transform.apply(cell);
+            (x + offset.0, y + offset.1)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TRANSFORMS: [Transform; 7] = [
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::FlipDiagonal,
+        Transform::Translate(3, -2),
+    ];
+
+    #[test]
+    fn hexagonal_symmetries() {
+        let mut neighbours = vec![(-1, 0), (1, 0), (0, -1), (0, 1), (-1, 1), (1, -1)];
+        neighbours.sort_unstable();
+        for transform in TRANSFORMS {
+            let mut moved: Vec<(i32, i32)> = neighbours
+                .iter()
+                .map(|&cell| match transform {
+                    Transform::Translate(..) => cell,
+                    transform => transform.apply(cell),
+                })
+                .collect();
+            moved.sort_unstable();
+            assert_eq!(
+                moved == neighbours,
+                transform.is_hexagonal(),
+                "{:?}",
+                transform
+            );
+        }
+    }
+
+    #[test]
+    fn inverse_restores_cells() {
+        let area = (-2, 5, 4, 8);
+        let cells = vec![(-2, 5), (0, 6), (4, 8), (1, 7)];
+        for transform in TRANSFORMS {
+            let moved = transform_cells(&cells, area, transform);
+            assert_ne!(moved, cells, "{:?}", transform);
+            let moved_area = transform.apply_to_area(area);
+            let back = transform_cells(&moved, moved_area, transform.inverse());
+            assert_eq!(back, cells, "{:?}", transform);
+            assert_eq!(transform.inverse().apply_to_area(moved_area), area);
+        }
+
+        // four quarter turns go back to the start
+        let (mut turned, mut turned_area) = (cells.clone(), area);
+        for _ in 0..4 {
+            turned = transform_cells(&turned, turned_area, Transform::Rotate90);
+            turned_area = Transform::Rotate90.apply_to_area(turned_area);
+        }
+        assert_eq!(turned, cells);
+        assert_eq!(turned_area, area);
+    }
+
+    #[test]
+    fn cells_stay_in_the_new_area() {
+        // an L in a 3x2 area
+        //  o..
+        //  ooo
+        let area = (0, 0, 2, 1);
+        let cells = [(0, 1), (0, 0), (1, 0), (2, 0)];
+
+        let rotated = transform_cells(&cells, area, Transform::Rotate90);
+        // clockwise the L becomes
+        //  oo
+        //  o.
+        //  o.
+        assert_eq!(Transform::Rotate90.apply_to_area(area), (0, 0, 1, 2));
+        assert_eq!(rotated, vec![(1, 2), (0, 2), (0, 1), (0, 0)]);
+
+        let flipped = transform_cells(&cells, area, Transform::FlipHorizontal);
+        assert_eq!(Transform::FlipHorizontal.apply_to_area(area), area);
+        assert_eq!(flipped, vec![(2, 1), (2, 0), (1, 0), (0, 0)]);
+
+        for transform in TRANSFORMS {
+            let (min_x, min_y, max_x, max_y) = transform.apply_to_area(area);
+            for (x, y) in transform_cells(&cells, area, transform) {
+                assert!(x >= min_x && x <= max_x && y >= min_y && y <= max_y);
+            }
+        }
+        assert_eq!(bounding_box(&cells), Some(area));
+        assert_eq!(bounding_box(&[]), None);
+    }
+}
@@ -0,0 +1,699 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use super::formats::Pattern;
+use super::game_of_life::GameOfLife;
+use super::hashlife::HashLife;
+use super::rule::Rule;
+use super::soup::Soup;
+use super::stability::Stability;
+use super::tiled_life::TiledLife;
+use super::topology::Topology;
+use super::transform::{Transform, transform_cells};
+
+/// most cells made alive at once by filling an area
+pub const MAX_FILLED_CELLS: i64 = 1 << 20;
+
+/// operations shared by every simulation engine
+/// the window and the headless runner only use the engines through this trait
+/// engines without history or stability detection keep the default methods
+pub trait Universe {
+    /// do a step in the simulation
+    fn step(&mut self);
+
+    /// advance the simulation by a number of generations
+    /// engines that can skip generations override the loop of steps
+    fn advance(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+
+    fn get_rule(&self) -> Rule;
+
+    /// fails without changing the rule if the engine can't simulate it
+    fn set_rule(&mut self, rule: Rule) -> Result<(), String>;
+
+    /// returns true if the cell in a given position is alive
+    fn get_cell_status(&self, pos_x: i32, pos_y: i32) -> bool;
+
+    /// make the cell in a given position alive or dead
+    fn set_cell_status(&mut self, pos_x: i32, pos_y: i32, alive: bool);
+
+    /// get the state of the cell in a given position
+    /// 0 is dead, 1 alive and the next states are those of rules with more states
+    fn get_cell_state(&self, pos_x: i32, pos_y: i32) -> u8 {
+        self.get_cell_status(pos_x, pos_y) as u8
+    }
+
+    /// change the state of the cell in a given position
+    /// states the rule doesn't have are ignored
+    fn set_cell_state(&mut self, pos_x: i32, pos_y: i32, state: u8) {
+        match state {
+            0 => self.set_cell_status(pos_x, pos_y, false),
+            1 => self.set_cell_status(pos_x, pos_y, true),
+            _ => {}
+        }
+    }
+
+    /// change the cell status from dead to alive or viceversa
+    fn change_cell_status(&mut self, pos_x: i32, pos_y: i32) {
+        let alive = self.get_cell_status(pos_x, pos_y);
+        self.set_cell_status(pos_x, pos_y, !alive);
+    }
+
+    /// make every cell in the list alive or dead
+    fn set_cells(&mut self, cells: &[(i32, i32)], alive: bool) {
+        for &(x, y) in cells {
+            self.set_cell_status(x, y, alive);
+        }
+    }
+
+    /// randomize an area going from (min_x, min_y) to (max_x, max_y)
+    /// returns the seed of the soup so it can be recreated
+    fn randomize_area(&mut self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> u64 {
+        let soup = Soup::random((min_x, min_y, max_x, max_y));
+        self.add_soup(&soup);
+        soup.seed
+    }
+
+    /// make the cells of a soup alive
+    fn add_soup(&mut self, soup: &Soup) {
+        self.set_cells(&soup.get_cells(), true);
+    }
+
+    /// rotate, reflect or move the cells of an area (min_x, min_y, max_x, max_y)
+    /// keeping their states, the cells under the new area that aren't replaced are kept
+    /// returns the area taken by the cells after the transform
+    fn transform_area(
+        &mut self,
+        area: (i32, i32, i32, i32),
+        transform: Transform,
+    ) -> (i32, i32, i32, i32) {
+        let (cells, states): (Vec<(i32, i32)>, Vec<u8>) =
+            self.data_as_states(area).into_iter().unzip();
+        let moved = transform_cells(&cells, area, transform);
+        for &(x, y) in &cells {
+            self.set_cell_state(x, y, 0);
+        }
+        for (&(x, y), &state) in moved.iter().zip(&states) {
+            self.set_cell_state(x, y, state);
+        }
+        transform.apply_to_area(area)
+    }
+
+    /// kill every cell of an area (min_x, min_y, max_x, max_y), in any state
+    fn clear_area(&mut self, area: (i32, i32, i32, i32)) {
+        for (x, y) in self.data_as_states(area).into_keys() {
+            self.set_cell_state(x, y, 0);
+        }
+    }
+
+    /// make every cell of an area (min_x, min_y, max_x, max_y) alive
+    /// refuses areas with more than MAX_FILLED_CELLS cells without changing anything
+    fn fill_area(&mut self, area: (i32, i32, i32, i32)) -> Result<(), String> {
+        let (min_x, min_y, max_x, max_y) = area;
+        let width = (max_x as i64 - min_x as i64 + 1).max(0);
+        let height = (max_y as i64 - min_y as i64 + 1).max(0);
+        if width.saturating_mul(height) > MAX_FILLED_CELLS {
+            return Err(format!(
+                "can't fill {}x{} cells, the limit is {} cells",
+                width, height, MAX_FILLED_CELLS
+            ));
+        }
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.set_cell_status(x, y, true);
+            }
+        }
+        Ok(())
+    }
+
+    /// write the cells of a pattern moved by the offset
+    /// the mode decides how they combine with the cells under the bounding box of the pattern
+    fn paste(&mut self, pattern: &Pattern, offset: (i32, i32), mode: PasteMode) {
+        let Some((min_x, min_y, max_x, max_y)) = pattern.get_bounding_box() else {
+            return;
+        };
+        let area = (
+            min_x + offset.0,
+            min_y + offset.1,
+            max_x + offset.0,
+            max_y + offset.1,
+        );
+        let pasted: HashMap<(i32, i32), u8> = pattern
+            .cells
+            .iter()
+            .map(|&cell| (cell, 1))
+            .chain(pattern.states.iter().cloned())
+            .map(|((x, y), state)| ((x + offset.0, y + offset.1), state))
+            .collect();
+        let old_cells = self.data_as_states(area);
+        // cells dead in both stay dead in every mode
+        let cells: HashSet<(i32, i32)> = old_cells.keys().chain(pasted.keys()).cloned().collect();
+        for (x, y) in cells {
+            let old = old_cells.get(&(x, y)).copied().unwrap_or(0);
+            let new = mode.combine(old, pasted.get(&(x, y)).copied().unwrap_or(0));
+            if old != new {
+                self.set_cell_state(x, y, new);
+            }
+        }
+    }
+
+    /// delete all alive cells and restart the step counter
+    fn clear_cells(&mut self);
+
+    /// count the cells that aren't dead, in any state
+    fn count_alive_cells(&self) -> usize;
+
+    fn get_steps_count(&self) -> u64;
+
+    /// get the smallest area (min_x, min_y, max_x, max_y) containing every alive cell
+    fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)>;
+
+    /// get a new hashset with the alive cell in a certain area
+    fn data_as_vec(&self, area: (i32, i32, i32, i32)) -> HashSet<(i32, i32)>;
+
+    /// get the state of the cells in a certain area that aren't dead
+    /// 1 is alive and the next states are the decay states of Generations rules
+    /// or the states of a rule table
+    fn data_as_states(&self, area: (i32, i32, i32, i32)) -> HashMap<(i32, i32), u8> {
+        self.data_as_vec(area)
+            .into_iter()
+            .map(|cell| (cell, 1))
+            .collect()
+    }
+
+    /// shape of the universe, the infinite plane if the engine has no topologies
+    fn get_topology(&self) -> Topology {
+        Topology::Infinite
+    }
+
+    /// whether the cells have become static, periodic or a translating pattern
+    fn get_stability(&self) -> Stability {
+        Stability::Evolving
+    }
+
+    /// change the number of previous states kept to undo
+    fn set_history_limit(&mut self, _limit: usize) {}
+
+    /// save the current state before an edit
+    fn record_edit(&mut self) {}
+
+    /// go back to the last recorded state, returns false if there isn't any
+    fn undo(&mut self) -> bool {
+        false
+    }
+
+    /// go forward to the last undone state, returns false if there isn't any
+    fn redo(&mut self) -> bool {
+        false
+    }
+
+    /// go back to the state before the last step, returns false if there isn't any
+    fn step_back(&mut self) -> bool {
+        false
+    }
+}
+
+impl Universe for GameOfLife {
+    fn step(&mut self) {
+        GameOfLife::step(self)
+    }
+
+    fn get_rule(&self) -> Rule {
+        GameOfLife::get_rule(self)
+    }
+
+    fn set_rule(&mut self, rule: Rule) -> Result<(), String> {
+        GameOfLife::set_rule(self, rule);
+        Ok(())
+    }
+
+    fn get_cell_status(&self, pos_x: i32, pos_y: i32) -> bool {
+        GameOfLife::get_cell_status(self, pos_x, pos_y)
+    }
+
+    fn set_cell_status(&mut self, pos_x: i32, pos_y: i32, alive: bool) {
+        GameOfLife::set_cell_status(self, pos_x, pos_y, alive)
+    }
+
+    fn get_cell_state(&self, pos_x: i32, pos_y: i32) -> u8 {
+        GameOfLife::get_cell_state(self, pos_x, pos_y)
+    }
+
+    fn set_cell_state(&mut self, pos_x: i32, pos_y: i32, state: u8) {
+        GameOfLife::set_cell_state(self, pos_x, pos_y, state)
+    }
+
+    fn change_cell_status(&mut self, pos_x: i32, pos_y: i32) {
+        GameOfLife::change_cell_status(self, pos_x, pos_y)
+    }
+
+    fn randomize_area(&mut self, min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> u64 {
+        GameOfLife::randomize_area(self, min_x, min_y, max_x, max_y)
+    }
+
+    fn add_soup(&mut self, soup: &Soup) {
+        GameOfLife::add_soup(self, soup)
+    }
+
+    fn clear_cells(&mut self) {
+        GameOfLife::clear_cells(self)
+    }
+
+    fn count_alive_cells(&self) -> usize {
+        GameOfLife::count_alive_cells(self)
+    }
+
+    fn get_steps_count(&self) -> u64 {
+        GameOfLife::get_steps_count(self)
+    }
+
+    fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)> {
+        GameOfLife::get_bounding_box(self)
+    }
+
+    fn data_as_vec(&self, area: (i32, i32, i32, i32)) -> HashSet<(i32, i32)> {
+        GameOfLife::data_as_vec(self, area)
+    }
+
+    fn data_as_states(&self, area: (i32, i32, i32, i32)) -> HashMap<(i32, i32), u8> {
+        GameOfLife::data_as_states(self, area)
+    }
+
+    fn get_topology(&self) -> Topology {
+        GameOfLife::get_topology(self)
+    }
+
+    fn get_stability(&self) -> Stability {
+        GameOfLife::get_stability(self)
+    }
+
+    fn set_history_limit(&mut self, limit: usize) {
+        GameOfLife::set_history_limit(self, limit)
+    }
+
+    fn record_edit(&mut self) {
+        GameOfLife::record_edit(self)
+    }
+
+    fn undo(&mut self) -> bool {
+        GameOfLife::undo(self)
+    }
+
+    fn redo(&mut self) -> bool {
+        GameOfLife::redo(self)
+    }
+
+    fn step_back(&mut self) -> bool {
+        GameOfLife::step_back(self)
+    }
+}
+
+impl Universe for TiledLife {
+    fn step(&mut self) {
+        TiledLife::step(self)
+    }
+
+    fn get_rule(&self) -> Rule {
+        TiledLife::get_rule(self)
+    }
+
+    fn set_rule(&mut self, rule: Rule) -> Result<(), String> {
+        TiledLife::set_rule(self, rule)
+    }
+
+    fn get_cell_status(&self, pos_x: i32, pos_y: i32) -> bool {
+        TiledLife::get_cell_status(self, pos_x, pos_y)
+    }
+
+    fn set_cell_status(&mut self, pos_x: i32, pos_y: i32, alive: bool) {
+        TiledLife::set_cell_status(self, pos_x, pos_y, alive)
+    }
+
+    fn clear_cells(&mut self) {
+        TiledLife::clear_cells(self)
+    }
+
+    fn count_alive_cells(&self) -> usize {
+        TiledLife::count_alive_cells(self)
+    }
+
+    fn get_steps_count(&self) -> u64 {
+        TiledLife::get_steps_count(self)
+    }
+
+    fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)> {
+        TiledLife::get_bounding_box(self)
+    }
+
+    fn data_as_vec(&self, area: (i32, i32, i32, i32)) -> HashSet<(i32, i32)> {
+        TiledLife::data_as_vec(self, area)
+    }
+}
+
+impl Universe for HashLife {
+    fn step(&mut self) {
+        HashLife::step(self)
+    }
+
+    /// stops early if the pattern reaches the edges of the largest universe
+    fn advance(&mut self, generations: u64) {
+        // the generations advanced before an error are kept
+        let _ = HashLife::advance_generations(self, generations);
+    }
+
+    fn get_rule(&self) -> Rule {
+        HashLife::get_rule(self)
+    }
+
+    fn set_rule(&mut self, rule: Rule) -> Result<(), String> {
+        HashLife::set_rule(self, rule)
+    }
+
+    fn get_cell_status(&self, pos_x: i32, pos_y: i32) -> bool {
+        HashLife::get_cell(self, pos_x, pos_y)
+    }
+
+    fn set_cell_status(&mut self, pos_x: i32, pos_y: i32, alive: bool) {
+        HashLife::set_cell(self, pos_x, pos_y, alive)
+    }
+
+    fn clear_cells(&mut self) {
+        HashLife::clear_cells(self)
+    }
+
+    fn count_alive_cells(&self) -> usize {
+        HashLife::count_alive_cells(self)
+    }
+
+    fn get_steps_count(&self) -> u64 {
+        HashLife::get_steps_count(self)
+    }
+
+    fn get_bounding_box(&self) -> Option<(i32, i32, i32, i32)> {
+        HashLife::get_bounding_box(self)
+    }
+
+    fn data_as_vec(&self, area: (i32, i32, i32, i32)) -> HashSet<(i32, i32)> {
+        HashLife::data_as_vec(self, area)
+    }
+}
+
+/// ways to combine pasted cells with the cells already in the universe
+/// cells that aren't dead keep the pasted state, or the old one if the pasted cell is dead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PasteMode {
+    /// not dead if any of them isn't dead
+    #[default]
+    Or,
+    /// not dead if both aren't dead
+    And,
+    /// not dead if only one of them isn't dead
+    Xor,
+    /// the pasted cells replace the old ones
+    Copy,
+}
+
+impl PasteMode {
+    /// get the state of a cell that was old and is pasted as new
+    pub fn combine(self, old: u8, new: u8) -> u8 {
+        let kept = match self {
+            PasteMode::Or => old != 0 || new != 0,
+            PasteMode::And => old != 0 && new != 0,
+            PasteMode::Xor => (old != 0) != (new != 0),
+            PasteMode::Copy => return new,
+        };
+        match (kept, new) {
+            (false, _) => 0,
+            (true, 0) => old,
+            (true, new) => new,
+        }
+    }
+
+    /// the following mode, going back to the first after the last one
+    pub fn next(self) -> PasteMode {
+        match self {
+            PasteMode::Or => PasteMode::And,
+            PasteMode::And => PasteMode::Xor,
+            PasteMode::Xor => PasteMode::Copy,
+            PasteMode::Copy => PasteMode::Or,
+        }
+    }
+}
+
+impl fmt::Display for PasteMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PasteMode::Or => write!(f, "OR"),
+            PasteMode::And => write!(f, "AND"),
+            PasteMode::Xor => write!(f, "XOR"),
+            PasteMode::Copy => write!(f, "COPY"),
+        }
+    }
+}
+
+/// available simulation engines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// set of alive cells, supports topologies, history and stability detection
+    #[default]
+    Sparse,
+    /// bitboard tiles, for dense soups
+    Tiled,
+    /// memoized quadtree, for huge repetitive patterns
+    HashLife,
+}
+
+impl Engine {
+    /// check that the engine can simulate a rule on a topology
+    /// the other engines than Sparse only simulate Life-like rules on the infinite plane
+    pub fn supports(self, rule: &Rule, topology: Topology) -> Result<(), String> {
+        if self == Engine::Sparse {
+            return Ok(());
+        }
+        if !rule.is_life_like() {
+            return Err(format!(
+                "the {} engine only simulates Life-like rules, use sparse for {}",
+                self, rule
+            ));
+        }
+        if topology != Topology::Infinite {
+            return Err(format!(
+                "the {} engine only simulates the infinite plane, use sparse for {}",
+                self, topology
+            ));
+        }
+        Ok(())
+    }
+
+    /// move the state of a universe into a new one of this engine
+    /// the rule, cells and step counter are kept
+    /// fails if the engine can't simulate the rule or topology of the universe,
+    /// or if it has a history since only Sparse can undo
+    pub fn build(self, gol: GameOfLife) -> Result<Box<dyn Universe + Send + Sync>, String> {
+        self.supports(&gol.get_rule(), gol.get_topology())?;
+        if self != Engine::Sparse && gol.get_history_limit() > 0 {
+            return Err(format!("the {} engine can't undo, use sparse", self));
+        }
+        Ok(match self {
+            Engine::Sparse => Box::new(gol),
+            Engine::Tiled => Box::new(TiledLife::try_from(&gol)?),
+            Engine::HashLife => Box::new(HashLife::try_from(&gol)?),
+        })
+    }
+}
+
+impl FromStr for Engine {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "sparse" => Ok(Engine::Sparse),
+            "tiled" => Ok(Engine::Tiled),
+            "hashlife" => Ok(Engine::HashLife),
+            _ => Err(format!(
+                "unknown engine '{}', use sparse, tiled or hashlife",
+                name
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Engine::Sparse => write!(f, "sparse"),
+            Engine::Tiled => write!(f, "tiled"),
+            Engine::HashLife => write!(f, "hashlife"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn engines_match() {
+        let mut gol = GameOfLife::with_rule(Rule::parse("B36/S23").unwrap());
+        gol.add_soup(&Soup::new(3, (-20, -20, 20, 20)));
+        let mut universes: Vec<Box<dyn Universe + Send + Sync>> =
+            [Engine::Sparse, Engine::Tiled, Engine::HashLife]
+                .into_iter()
+                .map(|engine| {
+                    let mut copy = GameOfLife::with_rule(gol.get_rule());
+                    for &(x, y) in gol.get_alive_cells() {
+                        copy.set_cell_status(x, y, true);
+                    }
+                    engine.build(copy).unwrap()
+                })
+                .collect();
+
+        for universe in universes.iter_mut() {
+            universe.set_cells(&[(100, 100), (101, 100), (102, 100)], true);
+            universe.change_cell_status(102, 100);
+            for _ in 0..10 {
+                universe.step();
+            }
+            universe.advance(90);
+        }
+
+        let area = (-100, -100, 200, 200);
+        for universe in &universes[1..] {
+            assert_eq!(universe.get_steps_count(), 100);
+            assert_eq!(universe.get_rule(), universes[0].get_rule());
+            assert_eq!(universe.data_as_vec(area), universes[0].data_as_vec(area));
+            assert_eq!(universe.get_bounding_box(), universes[0].get_bounding_box());
+        }
+    }
+
+    #[test]
+    fn transform_area_of_every_engine() {
+        for engine in [Engine::Sparse, Engine::Tiled, Engine::HashLife] {
+            let mut universe = engine.build(GameOfLife::new()).unwrap();
+            // a blinker inside the area and a cell outside that must not move
+            universe.set_cells(&[(0, 1), (1, 1), (2, 1), (10, 10)], true);
+            let area = universe.transform_area((0, 0, 2, 2), Transform::Rotate90);
+            assert_eq!(area, (0, 0, 2, 2));
+            let expected = HashSet::from([(1, 0), (1, 1), (1, 2)]);
+            assert_eq!(universe.data_as_vec(area), expected, "{}", engine);
+            assert!(universe.get_cell_status(10, 10));
+
+            let area = universe.transform_area(area, Transform::Translate(5, 0));
+            assert_eq!(area, (5, 0, 7, 2));
+            assert_eq!(universe.count_alive_cells(), 4);
+            assert!(universe.get_cell_status(6, 2));
+        }
+    }
+
+    #[test]
+    fn paste_modes() {
+        // the old cells are a column and the pasted ones a row crossing it in (1, 1)
+        let row = Pattern {
+            cells: vec![(0, 0), (1, 0), (2, 0)],
+            ..Default::default()
+        };
+        let expected = [
+            (PasteMode::Or, vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)]),
+            (PasteMode::And, vec![(1, 0), (1, 1), (1, 2)]),
+            (PasteMode::Xor, vec![(0, 1), (1, 0), (1, 2), (2, 1)]),
+            (
+                PasteMode::Copy,
+                vec![(0, 1), (1, 0), (1, 1), (1, 2), (2, 1)],
+            ),
+        ];
+        for (mode, expected) in expected {
+            let mut gol = GameOfLife::new();
+            gol.set_cells(&[(1, 0), (1, 1), (1, 2)], true);
+            gol.paste(&row, (0, 1), mode);
+            let mut cells: Vec<(i32, i32)> = gol.get_alive_cells().iter().cloned().collect();
+            cells.sort_unstable();
+            assert_eq!(cells, expected, "{}", mode);
+        }
+
+        // copy kills the old cells under the pattern
+        let mut gol = GameOfLife::new();
+        gol.fill_area((0, 0, 2, 2)).unwrap();
+        assert!(gol.fill_area((0, 0, 2000, 2000)).is_err());
+        assert!(
+            gol.fill_area((i32::MIN, i32::MIN, i32::MAX, i32::MAX))
+                .is_err()
+        );
+        let gap = Pattern {
+            cells: vec![(0, 0), (2, 0)],
+            ..Default::default()
+        };
+        gol.paste(&gap, (0, 1), PasteMode::Copy);
+        assert_eq!(gol.count_alive_cells(), 8);
+        assert!(!gol.get_cell_status(1, 1));
+        gol.clear_area((0, 0, 1, 2));
+        assert_eq!(gol.count_alive_cells(), 3);
+        assert_eq!(PasteMode::Copy.next(), PasteMode::Or);
+    }
+
+    #[test]
+    fn wireworld_copy_and_paste() {
+        // a head, a tail and two wires
+        let mut gol = GameOfLife::with_rule(Rule::parse("Wireworld").unwrap());
+        for (x, state) in [(0, 1), (1, 2), (2, 3), (3, 3)] {
+            gol.set_cell_state(x, 0, state);
+        }
+        let area = (0, 0, 3, 0);
+        let copied = Pattern::from_area(&gol, area);
+        gol.clear_area(area);
+        assert_eq!(gol.count_alive_cells(), 0);
+
+        gol.paste(&copied, (10, 5), PasteMode::Or);
+        let expected = HashMap::from([((10, 5), 1), ((11, 5), 2), ((12, 5), 3), ((13, 5), 3)]);
+        assert_eq!(gol.data_as_states((0, 0, 20, 20)), expected);
+
+        // the old states are kept under the dead pasted cells and replaced by the others
+        let gap = Pattern {
+            cells: vec![(0, 0)],
+            states: vec![((3, 0), 2)],
+            ..Default::default()
+        };
+        gol.paste(&gap, (10, 5), PasteMode::Or);
+        assert_eq!(gol.get_cell_state(12, 5), 3);
+        assert_eq!(gol.get_cell_state(13, 5), 2);
+        gol.paste(&gap, (10, 5), PasteMode::Xor);
+        assert_eq!(gol.get_cell_state(10, 5), 0);
+        assert_eq!(gol.get_cell_state(11, 5), 2);
+
+        let area = gol.transform_area((10, 5, 13, 5), Transform::FlipHorizontal);
+        assert_eq!(area, (10, 5, 13, 5));
+        let expected = HashMap::from([((13, 5), 0), ((12, 5), 2), ((11, 5), 3), ((10, 5), 0)]);
+        for ((x, y), state) in expected {
+            assert_eq!(gol.get_cell_state(x, y), state);
+        }
+    }
+
+    #[test]
+    fn parse_engine() {
+        assert_eq!("HashLife".parse(), Ok(Engine::HashLife));
+        assert_eq!(Engine::Tiled.to_string().parse(), Ok(Engine::Tiled));
+        assert!("fast".parse::<Engine>().is_err());
+        assert!(!Engine::Tiled.build(GameOfLife::new()).unwrap().undo());
+
+        // the other engines than sparse refuse what they can't simulate
+        let gol = GameOfLife::with_rule(Rule::parse("/2/3").unwrap());
+        assert!(Engine::Tiled.build(gol).is_err());
+        let mut gol = GameOfLife::new();
+        gol.set_topology(Topology::parse("T10,10").unwrap());
+        assert!(Engine::HashLife.build(gol).is_err());
+        let mut gol = GameOfLife::new();
+        gol.set_history_limit(10);
+        assert!(Engine::HashLife.build(gol).is_err());
+        let mut gol = GameOfLife::with_rule(Rule::parse("/2/3").unwrap());
+        gol.set_history_limit(10);
+        assert!(Engine::Sparse.build(gol).is_ok());
+
+        // nor can their rule be changed to one they can't simulate
+        let mut universe = Engine::HashLife.build(GameOfLife::new()).unwrap();
+        assert!(universe.set_rule(Rule::parse("/2/3").unwrap()).is_err());
+        assert_eq!(universe.get_rule(), Rule::conway());
+    }
+}
@@ -0,0 +1,192 @@
+use std::fs;
+use std::thread;
+
+use game_of_life::model::census::Census;
+use game_of_life::model::rule::Rule;
+use game_of_life::model::soup::{Soup, Symmetry};
+
+const USAGE: &str = "usage: game-of-life --search [options]
+
+options:
+  -n, --soups N         number of soups to search (default 100)
+  --seed N              seed of the first soup, the next ones use the following seeds (default 0)
+  -r, --rule RULE       rule of the soups (default B3/S23)
+  -d, --density D       fraction of alive cells in the soups (default 0.5)
+  -y, --symmetry S      symmetry of the soups: C1, C2, C4, D2, D4 or D8 (default C1)
+  --size N              side of the soups (default 16)
+  -g, --generations N   max generations to stabilize each soup and object (default 10000)
+  -o, --output FILE     write the census to a file instead of printing it";
+
+/// options of the soup search
+#[derive(Debug, PartialEq)]
+struct Options {
+    soups: u64,
+    seed: u64,
+    rule: Rule,
+    density: f64,
+    symmetry: Symmetry,
+    size: i32,
+    generations: u64,
+    output: Option<String>,
+}
+
+/// run many seeded soups, count the objects they leave and write the census
+/// the soups are shared between every core
+/// only prints the usage when called with --help
+pub fn run(args: &[String]) -> Result<(), String> {
+    let Some(options) = parse_args(args)? else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+    let end = options
+        .seed
+        .checked_add(options.soups)
+        .ok_or(format!("too many soups after the seed {}", options.seed))?;
+
+    // each thread searches every n-th seed in its own census
+    let threads = thread::available_parallelism().map_or(1, |n| n.get()) as u64;
+    let mut census = Census::new(options.rule.clone());
+    let results: Vec<Census> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.min(options.soups))
+            .map(|first| {
+                let options = &options;
+                scope.spawn(move || {
+                    let mut census = Census::new(options.rule.clone());
+                    for seed in (options.seed + first..end).step_by(threads as usize) {
+                        let soup = Soup {
+                            seed,
+                            density: options.density,
+                            area: (0, 0, options.size - 1, options.size - 1),
+                            symmetry: options.symmetry,
+                        };
+                        census.add_soup(&soup, options.generations);
+                    }
+                    census
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("search thread panicked"))
+            .collect()
+    });
+    for result in &results {
+        census.merge(result);
+    }
+
+    match options.output {
+        Some(output) => fs::write(&output, census.to_string())
+            .map_err(|e| format!("can't write {}: {}", output, e))?,
+        None => print!("{}", census),
+    }
+    Ok(())
+}
+
+/// returns None if the usage is asked with --help
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        soups: 100,
+        seed: 0,
+        rule: Rule::conway(),
+        density: 0.5,
+        symmetry: Symmetry::C1,
+        size: 16,
+        generations: 10000,
+        output: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "-n" | "--soups" => {
+                let value = value()?;
+                options.soups = value
+                    .parse()
+                    .map_err(|_| format!("invalid number of soups '{}'", value))?;
+            }
+            "--seed" => {
+                let value = value()?;
+                options.seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed '{}'", value))?;
+            }
+            "-r" | "--rule" => {
+                options.rule = Rule::parse(value()?).map_err(|e| e.to_string())?;
+            }
+            "-d" | "--density" => {
+                let value = value()?;
+                options.density = value
+                    .parse()
+                    .ok()
+                    .filter(|density| (0.0..=1.0).contains(density))
+                    .ok_or(format!("invalid density '{}'", value))?;
+            }
+            "-y" | "--symmetry" => options.symmetry = value()?.parse()?,
+            "--size" => {
+                let value = value()?;
+                options.size = value
+                    .parse()
+                    .ok()
+                    .filter(|&size| size > 0)
+                    .ok_or(format!("invalid size '{}'", value))?;
+            }
+            "-g" | "--generations" => {
+                let value = value()?;
+                options.generations = value
+                    .parse()
+                    .map_err(|_| format!("invalid number of generations '{}'", value))?;
+            }
+            "-o" | "--output" => options.output = Some(value()?.clone()),
+            "-h" | "--help" => return Ok(None),
+            arg => return Err(format!("unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+    Ok(Some(options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        let options = parse_args(&args(&[
+            "-n",
+            "10",
+            "--seed",
+            "5",
+            "-y",
+            "D4",
+            "-d",
+            "0.3",
+            "-o",
+            "census.txt",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(options.soups, 10);
+        assert_eq!(options.seed, 5);
+        assert_eq!(options.symmetry, Symmetry::D4);
+        assert_eq!(options.density, 0.3);
+        assert_eq!(options.output, Some("census.txt".to_string()));
+        assert_eq!(options.size, 16);
+
+        assert!(parse_args(&args(&["-d", "2"])).is_err());
+        assert!(parse_args(&args(&["-y", "C3"])).is_err());
+        assert!(parse_args(&args(&["--size", "0"])).is_err());
+        assert!(parse_args(&args(&["-n"])).is_err());
+        assert!(parse_args(&args(&["pattern.rle"])).is_err());
+        assert_eq!(parse_args(&args(&["--help"])), Ok(None));
+    }
+
+    #[test]
+    fn seeds_overflow() {
+        let args = args(&["--seed", &u64::MAX.to_string(), "-n", "2"]);
+        assert!(run(&args).is_err());
+    }
+}
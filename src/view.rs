@@ -1 +1,2 @@
 pub mod screen;
+pub mod speed;
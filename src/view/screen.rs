@@ -1,11 +1,46 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use macroquad::prelude::*;
 
+use super::speed::{DEFAULT_SPEED, SPEEDS, Speed};
+use crate::model::formats::Pattern;
+use crate::model::stability::Stability;
+use crate::model::transform::Transform;
+use crate::model::universe::PasteMode;
+
 const MOVEMENT_RATE: f32 = 0.01;
 
+/// fraction of the view added or removed on each step of the mouse wheel
+const ZOOM_RATE: f32 = 0.1;
+
+/// minimum number of rows and columns shown when zooming in
+const MIN_CELLS: i32 = 2;
+
+/// maximum number of rows and columns shown when zooming out
+/// so each frame only reads and draws a bounded area
+const MAX_CELLS: i32 = 1000;
+
 const FOOTER_HEIGHT: f32 = 30.0;
 
+const SELECTION_COLOR: Color = Color::new(0.0, 0.47, 0.95, 0.25);
+
+const PASTE_COLOR: Color = Color::new(0.0, 0.47, 0.95, 0.6);
+
+/// colors of the first and last decay states of Generations rules
+const DECAY_START_COLOR: Color = Color::new(0.75, 0.1, 0.1, 1.0);
+const DECAY_END_COLOR: Color = Color::new(1.0, 0.85, 0.75, 1.0);
+
+/// edits of the selected area and the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionAction {
+    Copy,
+    Cut,
+    /// read the clipboard and start pasting it
+    Paste,
+    Clear,
+    Fill,
+}
+
 //pos* indicates the area of the simulation to show in the screen
 pub struct Screen {
     posx_min: i32,
@@ -15,10 +50,36 @@ pub struct Screen {
     paused: bool,
     reset: bool,
     randomize: bool,
+    undo: bool,
+    redo: bool,
+    step_back: bool,
+    single_step: bool,
+    speed: usize,
     cols: i32,
     rows: i32,
     cell_width: f32,
     cell_heigth: f32,
+    boundary: Option<(i32, i32, i32, i32)>,
+    states: u8,
+    palette: HashMap<u8, Color>,
+    hexagonal: bool,
+    drag_position: Option<(f32, f32)>,
+    pan_remainder: (f32, f32),
+    zoom_size: (f32, f32),
+    seed: Option<u64>,
+    selection: Option<(i32, i32, i32, i32)>,
+    selection_start: Option<(i32, i32)>,
+    transform: Option<Transform>,
+    action: Option<SelectionAction>,
+    pasting: Option<Pattern>,
+    paste_mode: PasteMode,
+    paste_position: Option<(i32, i32)>,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen::new()
+    }
 }
 
 impl Screen {
@@ -31,17 +92,43 @@ impl Screen {
             paused: true,
             reset: false,
             randomize: false,
+            undo: false,
+            redo: false,
+            step_back: false,
+            single_step: false,
+            speed: DEFAULT_SPEED,
             cols: 0,
             rows: 0,
             cell_width: 0.0,
             cell_heigth: 0.0,
+            boundary: None,
+            states: 2,
+            palette: HashMap::new(),
+            hexagonal: false,
+            drag_position: None,
+            pan_remainder: (0.0, 0.0),
+            zoom_size: (0.0, 0.0),
+            seed: None,
+            selection: None,
+            selection_start: None,
+            transform: None,
+            action: None,
+            pasting: None,
+            paste_mode: PasteMode::default(),
+            paste_position: None,
         }
     }
 
-    /// receives a hashset indicating the coords of the alive cells
+    /// receives a hashmap with the state of the cells that aren't dead
     /// the coords are relative to the bottom left corner
-    pub async fn draw_frame(&mut self, gol_data: HashSet<(i32, i32)>, step: u32, cells_alive: u32) {
-        clear_background(WHITE);
+    pub async fn draw_frame(
+        &mut self,
+        gol_data: HashMap<(i32, i32), u8>,
+        step: u64,
+        cells_alive: u32,
+        stability: Stability,
+    ) {
+        clear_background(self.palette.get(&0).copied().unwrap_or(WHITE));
         if gol_data.len() == 1 {
             draw_rectangle(0.0, 0.0, self.cell_width, self.cell_heigth, BLACK);
         }
@@ -50,18 +137,26 @@ impl Screen {
         self.cell_width = screen_width() / (self.cols as f32);
 
         // draw the cells
-        for cell in &gol_data {
-            let px = (cell.0 - self.posx_min - 1) as f32;
-            let py = (self.posy_max - cell.1) as f32;
-
+        for (&cell, &state) in &gol_data {
+            let (px, py) = self.get_cell_corner(cell);
             draw_rectangle(
-                px * self.cell_width,
-                py * self.cell_heigth,
+                px,
+                py,
                 self.cell_width,
                 self.cell_heigth,
-                BLACK,
+                self.get_state_color(state),
             );
         }
+        if let Some(boundary) = self.boundary {
+            self.draw_boundary(boundary);
+        }
+        if let Some(selection) = self.selection {
+            self.draw_selection(selection);
+        }
+        if let (Some(pattern), Some(position)) = (&self.pasting, self.get_grid_position()) {
+            self.draw_paste_preview(pattern, position);
+        }
+
         // check if paused
         if self.paused {
             self.draw_pause_icon();
@@ -69,25 +164,18 @@ impl Screen {
 
         // draw the mouse hover
         // if the position has a cell, change the colors
-        if let Some(mouse_poition) = self.get_mouse_position() {
-            let px = mouse_poition.0 as i32 + self.posx_min as i32 + 1;
-            let py = self.posy_max as i32 - mouse_poition.1 as i32;
-            let (color1, color2) = match gol_data.contains(&(px, py)) {
+        if let Some(position) = self.get_grid_position() {
+            let (color1, color2) = match gol_data.get(&position) == Some(&1) {
                 true => (GRAY, BLACK),
                 false => (BLACK, WHITE),
             };
 
-            draw_rectangle(
-                (mouse_poition.0 as f32) * self.cell_width,
-                (mouse_poition.1 as f32) * self.cell_heigth,
-                self.cell_width,
-                self.cell_heigth,
-                color1,
-            );
+            let (px, py) = self.get_cell_corner(position);
+            draw_rectangle(px, py, self.cell_width, self.cell_heigth, color1);
             let padding = (self.cell_width * 0.1, self.cell_heigth * 0.1);
             draw_rectangle(
-                (mouse_poition.0 as f32) * self.cell_width + padding.0,
-                (mouse_poition.1 as f32) * self.cell_heigth + padding.1,
+                px + padding.0,
+                py + padding.1,
                 self.cell_width - (padding.0 * 2.0),
                 self.cell_heigth - (padding.1 * 2.0),
                 color2,
@@ -95,10 +183,110 @@ impl Screen {
         }
 
         // draw footer
-        self.draw_footer(step, cells_alive);
+        self.draw_footer(step, cells_alive, stability);
         next_frame().await
     }
 
+    /// states in the palette use its color
+    /// otherwise alive cells are black, the decay states fade from red to the background
+    fn get_state_color(&self, state: u8) -> Color {
+        if let Some(&color) = self.palette.get(&state) {
+            return color;
+        }
+        if state <= 1 {
+            return BLACK;
+        }
+        let t = match self.states > 3 {
+            true => (state - 2) as f32 / (self.states - 3) as f32,
+            false => 0.0,
+        };
+        Color::new(
+            DECAY_START_COLOR.r + (DECAY_END_COLOR.r - DECAY_START_COLOR.r) * t,
+            DECAY_START_COLOR.g + (DECAY_END_COLOR.g - DECAY_START_COLOR.g) * t,
+            DECAY_START_COLOR.b + (DECAY_END_COLOR.b - DECAY_START_COLOR.b) * t,
+            1.0,
+        )
+    }
+
+    /// draw the edges of a bounded universe
+    /// the area goes from (min_x, min_y) to (max_x, max_y) inclusive
+    pub fn draw_boundary(&self, area: (i32, i32, i32, i32)) {
+        self.draw_area(area, None, RED);
+    }
+
+    /// draw the selected area over the cells
+    pub fn draw_selection(&self, area: (i32, i32, i32, i32)) {
+        self.draw_area(area, Some(SELECTION_COLOR), BLUE);
+    }
+
+    /// draw the edges of an area and fill it if there is a color
+    fn draw_area(&self, area: (i32, i32, i32, i32), fill: Option<Color>, color: Color) {
+        let corners = self.get_area_corners(area);
+        if let Some(fill) = fill {
+            draw_triangle(corners[0], corners[1], corners[2], fill);
+            draw_triangle(corners[0], corners[2], corners[3], fill);
+        }
+        for (i, start) in corners.iter().enumerate() {
+            let end = corners[(i + 1) % corners.len()];
+            draw_line(start.x, start.y, end.x, end.y, 2.0, color);
+        }
+    }
+
+    /// get the top left, top right, bottom right and bottom left corners of an area on the screen
+    /// areas of hexagonal grids are parallelograms whose sides cross the middle of the rows
+    fn get_area_corners(&self, area: (i32, i32, i32, i32)) -> [Vec2; 4] {
+        let (min_x, min_y, max_x, max_y) = area;
+        let left = (min_x - self.posx_min - 1) as f32;
+        let right = (max_x - self.posx_min) as f32;
+        let top = (self.posy_max - max_y) as f32 * self.cell_heigth;
+        let bottom = (self.posy_max - min_y + 1) as f32 * self.cell_heigth;
+        let (top_skew, bottom_skew) = match self.hexagonal {
+            true => (self.get_skew(max_y) + 0.25, self.get_skew(min_y) - 0.25),
+            false => (0.0, 0.0),
+        };
+        [
+            vec2((left + top_skew) * self.cell_width, top),
+            vec2((right + top_skew) * self.cell_width, top),
+            vec2((right + bottom_skew) * self.cell_width, bottom),
+            vec2((left + bottom_skew) * self.cell_width, bottom),
+        ]
+    }
+
+    /// get the top left corner of a cell on the screen
+    fn get_cell_corner(&self, (x, y): (i32, i32)) -> (f32, f32) {
+        let px = (x - self.posx_min - 1) as f32 + self.get_skew(y);
+        let py = (self.posy_max - y) as f32;
+        (px * self.cell_width, py * self.cell_heigth)
+    }
+
+    /// get how many cells a row is moved to the right
+    /// hexagonal grids are drawn with each row half a cell to the right of the row below,
+    /// so the neighbours of a cell are the six cells touching it
+    /// the rows are moved around the row 0, so they stay in place while the view moves
+    fn get_skew(&self, y: i32) -> f32 {
+        match self.hexagonal {
+            true => y as f32 / 2.0,
+            false => 0.0,
+        }
+    }
+
+    /// draw the pattern being pasted with its top left corner on a position
+    pub fn draw_paste_preview(&self, pattern: &Pattern, position: (i32, i32)) {
+        for &(x, y) in &pattern.cells {
+            let (px, py) = self.get_cell_corner((x + position.0, y + position.1));
+            draw_rectangle(px, py, self.cell_width, self.cell_heigth, PASTE_COLOR);
+        }
+        if let Some((min_x, min_y, max_x, max_y)) = pattern.get_bounding_box() {
+            let area = (
+                min_x + position.0,
+                min_y + position.1,
+                max_x + position.0,
+                max_y + position.1,
+            );
+            self.draw_area(area, None, BLUE);
+        }
+    }
+
     pub fn draw_pause_icon(&self) {
         draw_rectangle(20.0, 20.0, 10.0, 30.0, RED);
         draw_rectangle(40.0, 20.0, 10.0, 30.0, RED);
@@ -124,7 +312,7 @@ impl Screen {
         }
     }
 
-    pub fn draw_footer(&self, step: u32, cells_alive: u32) {
+    pub fn draw_footer(&self, step: u64, cells_alive: u32, stability: Stability) {
         draw_rectangle(
             0.0,
             screen_height() - FOOTER_HEIGHT,
@@ -135,13 +323,20 @@ impl Screen {
         let posx_mid = (self.posx_max + self.posx_min) / 2;
         let posy_mid = (self.posy_max + self.posy_min) / 2;
 
-        draw_text(
-            format!("STEP: {}     CELLS ALIVE: {}", step, cells_alive).as_str(),
-            5.0,
-            screen_height() - 7.0,
-            25.0,
-            BLACK,
+        let mut status = format!(
+            "STEP: {}     CELLS ALIVE: {}     SPEED: {}     {}",
+            step,
+            cells_alive,
+            self.get_speed(),
+            stability
         );
+        if let Some(seed) = self.seed {
+            status.push_str(&format!("     SEED: {}", seed));
+        }
+        if self.pasting.is_some() {
+            status.push_str(&format!("     PASTE: {}", self.paste_mode));
+        }
+        draw_text(status.as_str(), 5.0, screen_height() - 7.0, 25.0, BLACK);
 
         let pos_text = format!("POS:({},{})", posx_mid, posy_mid);
         draw_text(
@@ -153,27 +348,53 @@ impl Screen {
         );
     }
 
+    /// returns true on the frame a mouse button starts editing the cells
+    pub fn is_edit_started(&self) -> bool {
+        (is_mouse_button_pressed(MouseButton::Left) || is_mouse_button_pressed(MouseButton::Right))
+            && self.get_mouse_position().is_some()
+            && !is_selecting()
+            && self.pasting.is_none()
+    }
+
     /// get the grid position of the mouse
+    /// no cell is edited while shift is held to select or while pasting
     pub fn mouse_clicked_pos(&mut self) -> Option<(i32, i32)> {
+        if is_selecting() || self.pasting.is_some() {
+            return None;
+        }
         if is_mouse_button_pressed(MouseButton::Left) || is_mouse_button_down(MouseButton::Right) {
-            return match self.get_mouse_position() {
-                Some(pos) => Some((
-                    pos.0 as i32 + self.posx_min as i32 + 1,
-                    self.posy_max as i32 - pos.1 as i32,
-                )),
-                None => None,
-            };
+            return self.get_grid_position();
         }
         None
     }
 
+    /// get the cell under the mouse in the coords of the simulation
+    fn get_grid_position(&self) -> Option<(i32, i32)> {
+        self.get_mouse_position().map(|pos| {
+            let y = self.posy_max - pos.1 as i32;
+            let column = mouse_position().0 / self.cell_width - self.get_skew(y);
+            (column.floor() as i32 + self.posx_min + 1, y)
+        })
+    }
+
     /// check if a button has been pressed
     /// for arrows, move the view by a rate in a given direction
     /// I O for zoom
     /// P pause the game
     /// R reset the game
+    /// Z undo, Y redo
+    /// B pause and go back a generation
+    /// N pause and advance a generation
+    /// plus and minus change the speed
     /// C center the grid
     /// T randomize an areas
+    /// with a selection, period and comma rotate it clockwise and counterclockwise
+    /// H V D flip it horizontally, vertically or across the diagonal
+    /// hexagonal grids only flip across the diagonal, the other transforms change how it evolves
+    /// shift and the arrows move it, escape unselects
+    /// control and C X V copy, cut and paste, delete clears it and F fills it
+    /// while pasting the same keys rotate and flip the pasted cells
+    /// M changes the paste mode and escape cancels the paste
     pub fn check_buttons(&mut self) {
         let mut mov_y = match self.posy_max < 0 {
             true => ((self.posy_max - self.posy_min).abs() as f32 * MOVEMENT_RATE) as i32,
@@ -195,20 +416,24 @@ impl Screen {
 
         let mut refresh = false;
 
-        if is_key_down(KeyCode::Down) {
+        self.check_selection_buttons();
+
+        // with shift the arrows move the selection instead of the view
+        let move_view = !(is_selecting() && self.selection.is_some());
+        if move_view && is_key_down(KeyCode::Down) {
             refresh = true;
             self.posy_max -= mov_y;
             self.posy_min -= mov_y;
-        } else if is_key_down(KeyCode::Up) {
+        } else if move_view && is_key_down(KeyCode::Up) {
             refresh = true;
             self.posy_max += mov_y;
             self.posy_min += mov_y;
         }
-        if is_key_down(KeyCode::Left) {
+        if move_view && is_key_down(KeyCode::Left) {
             refresh = true;
             self.posx_max -= mov_x;
             self.posx_min -= mov_x;
-        } else if is_key_down(KeyCode::Right) {
+        } else if move_view && is_key_down(KeyCode::Right) {
             refresh = true;
             self.posx_max += mov_x;
             self.posx_min += mov_x;
@@ -240,10 +465,10 @@ impl Screen {
             let new_center_x = (self.posx_max + self.posx_min) / 2;
             let new_center_y = (self.posy_max + self.posy_min) / 2;
 
-            self.posx_max += (center_x - new_center_x) as i32;
-            self.posx_min += (center_x - new_center_x) as i32;
-            self.posy_max += (center_y - new_center_y) as i32;
-            self.posy_min += (center_y - new_center_y) as i32;
+            self.posx_max += center_x - new_center_x;
+            self.posx_min += center_x - new_center_x;
+            self.posy_max += center_y - new_center_y;
+            self.posy_min += center_y - new_center_y;
         }
         if is_key_pressed(KeyCode::P) {
             self.paused = !self.paused;
@@ -254,7 +479,27 @@ impl Screen {
         if is_key_pressed(KeyCode::T) {
             self.randomize = true;
         }
-        if is_key_pressed(KeyCode::C) {
+        if is_key_pressed(KeyCode::Z) {
+            self.undo = true;
+        }
+        if is_key_pressed(KeyCode::Y) {
+            self.redo = true;
+        }
+        if is_key_pressed(KeyCode::B) {
+            self.paused = true;
+            self.step_back = true;
+        }
+        if is_key_pressed(KeyCode::N) {
+            self.paused = true;
+            self.single_step = true;
+        }
+        if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+            self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+        }
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+            self.speed = self.speed.saturating_sub(1);
+        }
+        if is_key_pressed(KeyCode::C) && !is_control_down() {
             refresh = true;
             self.posx_min = -20;
             self.posy_min = -20;
@@ -262,44 +507,265 @@ impl Screen {
             self.posy_max = 20;
         }
         if refresh {
-            self.rows = match self.posy_max < 0 {
-                true => (self.posy_max - self.posy_min).abs() as i32,
-                false => (self.posy_max - self.posy_min) as i32,
-            };
+            self.set_area(self.posx_min, self.posy_min, self.posx_max, self.posy_max);
+        }
+    }
 
-            self.cols = match self.posx_max < 0 {
-                true => (self.posx_max - self.posx_min).abs() as i32,
-                false => (self.posx_max - self.posx_min) as i32,
-            };
+    /// keys that edit the selected cells or the cells being pasted
+    fn check_selection_buttons(&mut self) {
+        let actions = [
+            (KeyCode::C, SelectionAction::Copy),
+            (KeyCode::X, SelectionAction::Cut),
+            (KeyCode::V, SelectionAction::Paste),
+        ];
+        if is_control_down() {
+            for (key, action) in actions {
+                if is_key_pressed(key)
+                    && (self.selection.is_some() || action == SelectionAction::Paste)
+                {
+                    self.action = Some(action);
+                }
+            }
+            return;
+        }
+        if is_key_pressed(KeyCode::M) && self.pasting.is_some() {
+            self.paste_mode = self.paste_mode.next();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            match self.pasting.is_some() {
+                true => self.pasting = None,
+                false => self.selection = None,
+            }
+        }
+        if self.selection.is_none() && self.pasting.is_none() {
+            return;
+        }
 
-            self.cell_heigth = (screen_height() - FOOTER_HEIGHT) / (self.rows as f32);
-            self.cell_width = screen_width() / (self.cols as f32);
+        let keys = [
+            (KeyCode::Period, Transform::Rotate90),
+            (KeyCode::Comma, Transform::Rotate270),
+            (KeyCode::H, Transform::FlipHorizontal),
+            (KeyCode::V, Transform::FlipVertical),
+            (KeyCode::D, Transform::FlipDiagonal),
+        ];
+        for (key, transform) in keys {
+            if !is_key_pressed(key) || (self.hexagonal && !transform.is_hexagonal()) {
+                continue;
+            }
+            match &mut self.pasting {
+                Some(pattern) => pattern.transform(transform),
+                None => self.transform = Some(transform),
+            }
+        }
+        if self.selection.is_none() {
+            return;
+        }
+        if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace) {
+            self.action = Some(SelectionAction::Clear);
+        }
+        if is_key_pressed(KeyCode::F) {
+            self.action = Some(SelectionAction::Fill);
+        }
+        if is_selecting() {
+            let moves = [
+                (KeyCode::Up, Transform::Translate(0, 1)),
+                (KeyCode::Down, Transform::Translate(0, -1)),
+                (KeyCode::Left, Transform::Translate(-1, 0)),
+                (KeyCode::Right, Transform::Translate(1, 0)),
+            ];
+            for (key, transform) in moves {
+                if is_key_pressed(key) {
+                    self.transform = Some(transform);
+                }
+            }
+        }
+    }
+
+    /// the mouse wheel zooms in and out keeping the cell under the cursor in place
+    /// dragging with the middle button moves the view with the mouse
+    /// dragging with shift and the left button selects a rectangle
+    pub fn check_mouse(&mut self) {
+        let (mouse_x, mouse_y) = mouse_position();
+
+        if self.pasting.is_some() && is_mouse_button_pressed(MouseButton::Left) {
+            self.paste_position = self.get_grid_position();
+        } else if is_selecting() && is_mouse_button_pressed(MouseButton::Left) {
+            self.selection_start = self.get_grid_position();
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.selection_start = None;
+        }
+        if let (Some(start), Some(end)) = (self.selection_start, self.get_grid_position()) {
+            self.selection = Some((
+                start.0.min(end.0),
+                start.1.min(end.1),
+                start.0.max(end.0),
+                start.1.max(end.1),
+            ));
+        }
+
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 && self.get_mouse_position().is_some() {
+            self.zoom_at(mouse_x, mouse_y, wheel.signum());
+        }
+
+        if is_mouse_button_down(MouseButton::Middle) {
+            if let Some((last_x, last_y)) = self.drag_position {
+                self.pan(mouse_x - last_x, mouse_y - last_y);
+            }
+            self.drag_position = Some((mouse_x, mouse_y));
+        } else {
+            self.drag_position = None;
+            self.pan_remainder = (0.0, 0.0);
         }
     }
 
+    /// zoom in (direction 1) or out (direction -1) around a point of the screen
+    /// the size of the view is scaled without rounding, so small views zoom as smoothly as big ones
+    fn zoom_at(&mut self, mouse_x: f32, mouse_y: f32, direction: f32) {
+        let factor = 1.0 - direction * ZOOM_RATE;
+        let scale = |size: f32| (size * factor).clamp(MIN_CELLS as f32, MAX_CELLS as f32);
+        let zoom_size = (scale(self.zoom_size.0), scale(self.zoom_size.1));
+        let (cols, rows) = (zoom_size.0.round() as i32, zoom_size.1.round() as i32);
+
+        // position of the cursor in the grid, it must stay under the cursor
+        let grid_x = self.posx_min as f32 + 1.0 + mouse_x / self.cell_width;
+        let grid_y = self.posy_max as f32 - mouse_y / self.cell_heigth;
+
+        let posx_min = (grid_x - 1.0 - mouse_x / screen_width() * cols as f32).round() as i32;
+        let posy_max =
+            (grid_y + mouse_y / (screen_height() - FOOTER_HEIGHT) * rows as f32).round() as i32;
+        self.set_area(posx_min, posy_max - rows, posx_min + cols, posy_max);
+        self.zoom_size = zoom_size;
+    }
+
+    /// move the view following a mouse movement in pixels
+    /// the movements smaller than a cell are accumulated
+    fn pan(&mut self, dx: f32, dy: f32) {
+        self.pan_remainder.0 -= dx / self.cell_width;
+        self.pan_remainder.1 += dy / self.cell_heigth;
+
+        let cells_x = self.pan_remainder.0.trunc();
+        let cells_y = self.pan_remainder.1.trunc();
+        self.pan_remainder.0 -= cells_x;
+        self.pan_remainder.1 -= cells_y;
+
+        self.posx_min += cells_x as i32;
+        self.posx_max += cells_x as i32;
+        self.posy_min += cells_y as i32;
+        self.posy_max += cells_y as i32;
+    }
+
     /// set the view area and change the aditional information of the screen
+    /// the number of rows and columns is kept between MIN_CELLS and MAX_CELLS around the center
     pub fn set_area(&mut self, a: i32, b: i32, c: i32, d: i32) {
-        self.posx_min = a;
-        self.posy_min = b;
-        self.posx_max = c;
-        self.posy_max = d;
+        (self.posx_min, self.posx_max) = clamp_size(a, c);
+        (self.posy_min, self.posy_max) = clamp_size(b, d);
 
         self.rows = match self.posy_max < 0 {
-            true => (self.posy_max - self.posy_min).abs() as i32,
-            false => (self.posy_max - self.posy_min) as i32,
+            true => (self.posy_max - self.posy_min).abs(),
+            false => self.posy_max - self.posy_min,
         };
 
         self.cols = match self.posx_max < 0 {
-            true => (self.posx_max - self.posx_min).abs() as i32,
-            false => (self.posx_max - self.posx_min) as i32,
+            true => (self.posx_max - self.posx_min).abs(),
+            false => self.posx_max - self.posx_min,
         };
 
         self.cell_heigth = (screen_height() - FOOTER_HEIGHT) / (self.rows as f32);
         self.cell_width = screen_width() / (self.cols as f32);
+        self.zoom_size = (self.cols as f32, self.rows as f32);
+    }
+
+    /// set the board of a bounded universe, None for the infinite plane
+    pub fn set_boundary(&mut self, boundary: Option<(i32, i32, i32, i32)>) {
+        self.boundary = boundary;
+    }
+
+    /// set the number of states of the rule, to pick the colors of the decay states
+    pub fn set_states(&mut self, states: u8) {
+        self.states = states;
+    }
+
+    /// set the colors of the states of a rule table, the state 0 is the background
+    pub fn set_palette(&mut self, palette: HashMap<u8, Color>) {
+        self.palette = palette;
+    }
+
+    /// draw the grid skewed for rules of the hexagonal neighbourhood
+    pub fn set_hexagonal(&mut self, hexagonal: bool) {
+        self.hexagonal = hexagonal;
+    }
+
+    /// set the seed of the last random soup shown in the footer, None to hide it
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// get the selected area (min_x, min_y, max_x, max_y) if there is any
+    pub fn get_selection(&self) -> Option<(i32, i32, i32, i32)> {
+        self.selection
+    }
+
+    pub fn set_selection(&mut self, selection: Option<(i32, i32, i32, i32)>) {
+        self.selection = selection;
+    }
+
+    /// get the transform requested for the selection
+    pub fn get_transform(&self) -> Option<Transform> {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Option<Transform>) {
+        self.transform = transform;
     }
 
+    /// get the edit requested for the selection or the clipboard
+    pub fn get_action(&self) -> Option<SelectionAction> {
+        self.action
+    }
+
+    pub fn set_action(&mut self, action: Option<SelectionAction>) {
+        self.action = action;
+    }
+
+    /// start pasting a pattern, its preview follows the mouse until it's placed
+    /// None stops pasting
+    pub fn set_pasting(&mut self, pattern: Option<Pattern>) {
+        self.pasting = pattern;
+        self.paste_position = None;
+    }
+
+    pub fn get_pasting(&self) -> Option<&Pattern> {
+        self.pasting.as_ref()
+    }
+
+    pub fn get_paste_mode(&self) -> PasteMode {
+        self.paste_mode
+    }
+
+    /// get the position where the pattern being pasted was placed
+    /// with a click, the top left corner of the pattern goes there
+    pub fn get_paste_position(&self) -> Option<(i32, i32)> {
+        self.paste_position
+    }
+
+    /// get the area (min_x, min_y, max_x, max_y) of the cells shown on the screen
+    /// the rows of hexagonal grids are moved, so the area is wider than the view
     pub fn get_area(&self) -> (i32, i32, i32, i32) {
-        (self.posx_min, self.posy_min, self.posx_max, self.posy_max)
+        match self.hexagonal {
+            true => (
+                self.posx_min - (self.posy_max + 1).div_euclid(2),
+                self.posy_min,
+                self.posx_max + 1 - self.posy_min.div_euclid(2),
+                self.posy_max,
+            ),
+            false => (self.posx_min, self.posy_min, self.posx_max, self.posy_max),
+        }
     }
 
     pub fn is_paused(&self) -> bool {
@@ -321,4 +787,64 @@ impl Screen {
     pub fn set_random(&mut self, r: bool) {
         self.randomize = r;
     }
+
+    pub fn is_undo(&self) -> bool {
+        self.undo
+    }
+
+    pub fn set_undo(&mut self, u: bool) {
+        self.undo = u;
+    }
+
+    pub fn is_redo(&self) -> bool {
+        self.redo
+    }
+
+    pub fn set_redo(&mut self, r: bool) {
+        self.redo = r;
+    }
+
+    pub fn is_step_back(&self) -> bool {
+        self.step_back
+    }
+
+    pub fn set_step_back(&mut self, b: bool) {
+        self.step_back = b;
+    }
+
+    pub fn is_single_step(&self) -> bool {
+        self.single_step
+    }
+
+    pub fn set_single_step(&mut self, s: bool) {
+        self.single_step = s;
+    }
+
+    /// get the index of the current speed in SPEEDS
+    pub fn get_speed_level(&self) -> usize {
+        self.speed
+    }
+
+    pub fn get_speed(&self) -> Speed {
+        SPEEDS[self.speed]
+    }
+}
+
+/// move the ends of a side of the view so its size is between MIN_CELLS and MAX_CELLS
+/// keeping its center
+fn clamp_size(min: i32, max: i32) -> (i32, i32) {
+    let size = max - min;
+    let clamped = size.clamp(MIN_CELLS, MAX_CELLS);
+    let min = min + (size - clamped) / 2;
+    (min, min + clamped)
+}
+
+/// returns true while shift is held, the left button selects instead of editing
+fn is_selecting() -> bool {
+    is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)
+}
+
+/// returns true while control is held for the clipboard keys
+fn is_control_down() -> bool {
+    is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl)
 }
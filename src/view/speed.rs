@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// how fast the simulation runs
+/// delay is the time in milliseconds between updates, 0 runs as fast as possible
+/// generations is the number of steps done on each update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Speed {
+    pub delay: u64,
+    pub generations: u32,
+}
+
+/// available speeds from the slowest to the fastest
+pub const SPEEDS: [Speed; 10] = [
+    Speed::new(1000, 1),
+    Speed::new(500, 1),
+    Speed::new(200, 1),
+    Speed::new(100, 1),
+    Speed::new(50, 1),
+    Speed::new(20, 1),
+    Speed::new(10, 1),
+    Speed::new(0, 1),
+    Speed::new(0, 10),
+    Speed::new(0, 100),
+];
+
+/// index of the speed used at start, 10 generations per second
+pub const DEFAULT_SPEED: usize = 3;
+
+impl Speed {
+    pub const fn new(delay: u64, generations: u32) -> Self {
+        Speed { delay, generations }
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.delay, self.generations) {
+            (0, 1) => write!(f, "MAX"),
+            (0, generations) => write!(f, "MAX x{}", generations),
+            (delay, generations) => write!(f, "{} GEN/S", 1000 * generations as u64 / delay),
+        }
+    }
+}